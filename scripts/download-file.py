@@ -0,0 +1,16 @@
+import pathlib
+import sys
+import urllib.request
+
+
+def main():
+    url = sys.argv[1]
+    target = pathlib.Path(sys.argv[2])
+
+    target.parent.mkdir(parents=True, exist_ok=True)
+    with urllib.request.urlopen(url, timeout=300) as response:
+        target.write_bytes(response.read())
+
+
+if __name__ == "__main__":
+    main()
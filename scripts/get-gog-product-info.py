@@ -0,0 +1,31 @@
+import json
+import sys
+import urllib.error
+import urllib.request
+
+
+def get_product(product_id):
+    url = f"https://api.gog.com/products/{product_id}"
+    try:
+        with urllib.request.urlopen(url, timeout=30) as response:
+            data = json.load(response)
+    except urllib.error.HTTPError as e:
+        if e.code == 404:
+            return None
+        raise
+
+    return {
+        "game_type": data.get("game_type"),
+        "title": data.get("title"),
+    }
+
+
+def main():
+    product_ids = [int(arg) for arg in sys.argv[1:]]
+
+    info = {str(product_id): get_product(product_id) for product_id in product_ids}
+    print(json.dumps(info, indent=2))
+
+
+if __name__ == "__main__":
+    main()
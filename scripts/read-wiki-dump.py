@@ -0,0 +1,39 @@
+import json
+import sys
+import xml.etree.ElementTree as ET
+
+
+def local(tag):
+    return tag.rsplit("}", 1)[-1]
+
+
+def main():
+    # One JSON object per line, so that the reader can process pages as they arrive.
+    path = sys.argv[1]
+
+    for _, element in ET.iterparse(path, events=("end",)):
+        if local(element.tag) != "page":
+            continue
+
+        fields = {local(child.tag): child for child in element}
+        revision = fields.get("revision")
+        if fields.get("ns") is None or fields["ns"].text != "0" or "redirect" in fields or revision is None:
+            element.clear()
+            continue
+
+        revision_fields = {local(child.tag): child for child in revision}
+        print(
+            json.dumps(
+                {
+                    "title": fields["title"].text,
+                    "page_id": int(fields["id"].text),
+                    "timestamp": revision_fields["timestamp"].text,
+                    "text": revision_fields["text"].text or "",
+                }
+            )
+        )
+        element.clear()
+
+
+if __name__ == "__main__":
+    main()
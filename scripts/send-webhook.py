@@ -0,0 +1,20 @@
+import sys
+import urllib.request
+
+
+def main():
+    url = sys.argv[1]
+    payload = sys.argv[2].encode("utf-8")
+
+    request = urllib.request.Request(
+        url,
+        data=payload,
+        headers={"Content-Type": "application/json"},
+        method="POST",
+    )
+    with urllib.request.urlopen(request, timeout=30):
+        pass
+
+
+if __name__ == "__main__":
+    main()
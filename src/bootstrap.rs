@@ -0,0 +1,117 @@
+use std::{path::PathBuf, process::Command};
+
+use crate::{
+    epic::EpicCache,
+    manifest::Manifest,
+    repo,
+    resource::ResourceFile,
+    schema::{self, ManifestMeta},
+    steam::SteamCache,
+    wiki::{WikiCache, WikiMetaCache},
+};
+
+/// Where the published data files live, relative to the repository root.
+pub const DEFAULT_SOURCE: &str = "https://raw.githubusercontent.com/mtkennerly/ludusavi-manifest/master";
+
+/// A downloaded file that passed verification and is waiting to replace the local copy.
+struct Staged {
+    name: &'static str,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// Download the published manifest and caches, verify them,
+/// and only then replace the local copies.
+/// The local copies are left alone unless every file passes,
+/// so that the caches and manifest never come from different runs.
+/// Returns false if any file could not be downloaded or verified.
+pub fn run(source: &str) -> bool {
+    let staging = PathBuf::from(repo()).join("tmp/bootstrap");
+
+    let staged = [
+        fetch::<WikiCache>(source, &staging, |_, _| Ok(())),
+        fetch::<SteamCache>(source, &staging, |_, _| Ok(())),
+        fetch::<EpicCache>(source, &staging, |_, _| Ok(())),
+        fetch::<ManifestMeta>(source, &staging, |meta, _| {
+            if meta.schema_version != *schema::VERSION {
+                return Err(format!(
+                    "generated for schema version {}, but this checkout's schema is version {} (try updating the checkout)",
+                    meta.schema_version,
+                    *schema::VERSION
+                ));
+            }
+            Ok(())
+        }),
+        fetch::<Manifest>(source, &staging, |manifest, _| {
+            schema::validate_manifest(manifest).map_err(|e| e.to_string())
+        }),
+    ];
+
+    let Some(staged) = staged.into_iter().collect::<Option<Vec<_>>>() else {
+        println!("Keeping the local copies, since not every file could be downloaded and verified");
+        return false;
+    };
+
+    let mut ok = true;
+    for file in staged {
+        if let Err(e) = std::fs::rename(&file.from, &file.to) {
+            println!("{}: unable to replace the local copy: {}", file.name, e);
+            ok = false;
+        }
+    }
+
+    if ok && !WikiMetaCache::path().exists() {
+        // Start tracking recent changes from now, since the published caches are kept up to date.
+        WikiMetaCache::load().unwrap().save();
+    }
+
+    ok
+}
+
+fn fetch<T: ResourceFile>(
+    source: &str,
+    staging: &std::path::Path,
+    verify: impl Fn(&T, &str) -> Result<(), String>,
+) -> Option<Staged> {
+    let url = format!("{}/{}", source.trim_end_matches('/'), T::FILE_NAME);
+    let staged = staging.join(T::FILE_NAME);
+    println!("Downloading {}", url);
+
+    let output = Command::new("python")
+        .arg(format!("{}/scripts/download-file.py", repo()))
+        .arg(&url)
+        .arg(&staged)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            println!(
+                "{}: download failed: {}",
+                T::FILE_NAME,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return None;
+        }
+        Err(e) => {
+            println!("{}: download failed: {}", T::FILE_NAME, e);
+            return None;
+        }
+    }
+
+    let verified = T::load_raw(&staged)
+        .map_err(|e| format!("unable to read: {}", e))
+        .and_then(|raw| {
+            let data = T::load_from_string(&raw).map_err(|e| format!("invalid data: {}", e))?;
+            verify(&data, &raw)
+        });
+    if let Err(e) = verified {
+        println!("{}: {}", T::FILE_NAME, e);
+        return None;
+    }
+
+    Some(Staged {
+        name: T::FILE_NAME,
+        from: staged,
+        to: T::path(),
+    })
+}
@@ -0,0 +1,102 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// We can limit how many API calls we make per run,
+/// so that scheduled imports stay within API etiquette even after a large backlog accumulates.
+/// Any remaining work stays outdated for the next run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Api {
+    /// Each request to the wiki.
+    Wiki,
+    /// Each batch of Steam product info.
+    Steam,
+}
+
+impl Api {
+    fn index(&self) -> usize {
+        match self {
+            Self::Wiki => 0,
+            Self::Steam => 1,
+        }
+    }
+}
+
+static LIMITS: [AtomicUsize; 2] = [AtomicUsize::new(usize::MAX), AtomicUsize::new(usize::MAX)];
+static USED: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+pub fn set_limit(api: Api, limit: Option<usize>) {
+    LIMITS[api.index()].store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+pub fn spend(api: Api) {
+    USED[api.index()].fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn used(api: Api) -> usize {
+    USED[api.index()].load(Ordering::Relaxed)
+}
+
+/// This is checked before starting each unit of work,
+/// so a unit that's already in progress may go slightly over the limit.
+pub fn exhausted(api: Api) -> bool {
+    let exhausted = used(api) >= LIMITS[api.index()].load(Ordering::Relaxed);
+    if exhausted {
+        eprintln!("{:?} API budget exhausted ({} used)", api, used(api));
+    }
+    exhausted
+}
+
+/// Timing and API usage for one phase of a run.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseLog {
+    pub name: String,
+    pub milliseconds: u64,
+    pub wiki_requests: usize,
+    pub steam_batches: usize,
+}
+
+/// Timing and API usage for the most recent bulk run,
+/// so that we can monitor performance and API footprint over time.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLog {
+    pub started: Option<chrono::DateTime<chrono::Utc>>,
+    pub phases: Vec<PhaseLog>,
+}
+
+impl RunLog {
+    pub fn new() -> Self {
+        Self {
+            started: Some(chrono::Utc::now()),
+            phases: vec![],
+        }
+    }
+
+    pub fn start(&self, name: &str) -> Phase {
+        Phase {
+            name: name.to_string(),
+            start: Instant::now(),
+            wiki: used(Api::Wiki),
+            steam: used(Api::Steam),
+        }
+    }
+
+    pub fn finish(&mut self, phase: Phase) {
+        self.phases.push(PhaseLog {
+            name: phase.name,
+            milliseconds: phase.start.elapsed().as_millis() as u64,
+            wiki_requests: used(Api::Wiki) - phase.wiki,
+            steam_batches: used(Api::Steam) - phase.steam,
+        });
+    }
+}
+
+pub struct Phase {
+    name: String,
+    start: Instant,
+    wiki: usize,
+    steam: usize,
+}
@@ -0,0 +1,273 @@
+use crate::{
+    cli,
+    manifest::{placeholder, Manifest, ManifestOverride},
+    path::{BroadPaths, IgnorePaths},
+    resource::ResourceFile,
+    schema::{self, ManifestMeta},
+    steam::{SteamCache, SteamRoots},
+    wiki::{WikiCache, WikiLanguages, WikiPreprocess, WikiTemplates},
+};
+
+/// Validate the data files without any network access.
+/// Returns false if there are any problems.
+pub fn check_data_files() -> bool {
+    let mut problems = vec![];
+
+    let overrides = check_file::<ManifestOverride>(&mut problems);
+    let wiki_cache = check_file::<WikiCache>(&mut problems);
+    let steam_cache = check_file::<SteamCache>(&mut problems);
+    let manifest = check_file::<Manifest>(&mut problems);
+    if let Some(meta) = check_file::<ManifestMeta>(&mut problems) {
+        if meta.schema_version != *schema::VERSION {
+            problems.push(format!(
+                "{}: generated for schema version {}, but the schema is version {}",
+                ManifestMeta::FILE_NAME,
+                meta.schema_version,
+                *schema::VERSION
+            ));
+        }
+    }
+    if let Some(problem) = schema::version_problem() {
+        problems.push(problem);
+    }
+    check_file::<BroadPaths>(&mut problems);
+    check_file::<WikiLanguages>(&mut problems);
+    if let Some(steam_roots) = check_file::<SteamRoots>(&mut problems) {
+        for root in steam_roots.0.keys() {
+            if *root != root.to_lowercase() {
+                problems.push(format!("{}: key should be lowercase: {}", SteamRoots::FILE_NAME, root));
+            }
+        }
+        for (root, mapped) in steam_roots.invalid_mappings() {
+            problems.push(format!(
+                "{}: {} should start with a placeholder: {}",
+                SteamRoots::FILE_NAME,
+                root,
+                mapped
+            ));
+        }
+    }
+    if let Some(wiki_templates) = check_file::<WikiTemplates>(&mut problems) {
+        for tag in wiki_templates.0.keys() {
+            if *tag != tag.to_lowercase() {
+                problems.push(format!(
+                    "{}: key should be lowercase: {}",
+                    WikiTemplates::FILE_NAME,
+                    tag
+                ));
+            }
+        }
+    }
+    if let Some(wiki_preprocess) = check_file::<WikiPreprocess>(&mut problems) {
+        for rule in &wiki_preprocess.0 {
+            if let Err(e) = rule.regex() {
+                problems.push(format!("{}: invalid pattern: {}", WikiPreprocess::FILE_NAME, e));
+            }
+            for failure in rule.failed_tests() {
+                problems.push(format!("{}: {}: {}", WikiPreprocess::FILE_NAME, rule.pattern, failure));
+            }
+        }
+    }
+    let ignore_paths = check_file::<IgnorePaths>(&mut problems);
+
+    if let Some(ignore_paths) = &ignore_paths {
+        for rule in &ignore_paths.0 {
+            if let Err(e) = rule.regex() {
+                problems.push(format!("{}: invalid pattern: {}", IgnorePaths::FILE_NAME, e));
+            }
+        }
+    }
+
+    if let Some(overrides) = &overrides {
+        for (title, overridden) in &overrides.0 {
+            for error in schema::check(&overridden.game) {
+                problems.push(format!("{}: {}: {}", ManifestOverride::FILE_NAME, title, error));
+            }
+            for (path, entry) in &overridden.game.files {
+                for reason in entry.when.iter().filter_map(|x| x.contradiction()) {
+                    problems.push(format!(
+                        "{}: {}: contradictory constraint for {}: {}",
+                        ManifestOverride::FILE_NAME,
+                        title,
+                        path,
+                        reason
+                    ));
+                }
+            }
+            for executable in overridden.game.launch.keys() {
+                if !executable.starts_with(&format!("{}/", placeholder::BASE)) {
+                    problems.push(format!(
+                        "{}: {}: launch executable should start with {}: {}",
+                        ManifestOverride::FILE_NAME,
+                        title,
+                        placeholder::BASE,
+                        executable
+                    ));
+                }
+            }
+            if wiki_cache.as_ref().is_some_and(|x| !x.0.contains_key(title)) {
+                problems.push(format!(
+                    "{}: {}: not found in {}",
+                    ManifestOverride::FILE_NAME,
+                    title,
+                    WikiCache::FILE_NAME
+                ));
+            }
+        }
+    }
+
+    // These aren't necessarily a problem, so they don't fail the check.
+    let mut notes = vec![];
+    if let Some(wiki_cache) = &wiki_cache {
+        for titles in wiki_cache.titles_differing_by_case_or_punctuation() {
+            notes.push(format!(
+                "{}: titles only differ by case or punctuation: {}",
+                WikiCache::FILE_NAME,
+                titles.join(" | ")
+            ));
+        }
+    }
+
+    if let (Some(wiki_cache), Some(steam_cache)) = (&wiki_cache, &steam_cache) {
+        for (title, info) in &wiki_cache.0 {
+            if let Some(id) = info.steam {
+                if !steam_cache.0.contains_key(&id) {
+                    problems.push(format!(
+                        "{}: {}: Steam ID {} not found in {}",
+                        WikiCache::FILE_NAME,
+                        title,
+                        id,
+                        SteamCache::FILE_NAME
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(manifest) = &manifest {
+        for (title, game) in &manifest.0 {
+            if let Some(alias) = &game.alias {
+                match manifest.0.get(alias) {
+                    None => problems.push(format!(
+                        "{}: {}: alias target not found: {}",
+                        Manifest::FILE_NAME,
+                        title,
+                        alias
+                    )),
+                    Some(target) if target.alias.is_some() => problems.push(format!(
+                        "{}: {}: alias target is also an alias: {}",
+                        Manifest::FILE_NAME,
+                        title,
+                        alias
+                    )),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Finding<'a> {
+        level: &'static str,
+        message: &'a str,
+    }
+    let findings: Vec<_> = problems
+        .iter()
+        .map(|x| Finding {
+            level: "problem",
+            message: x,
+        })
+        .chain(notes.iter().map(|x| Finding {
+            level: "note",
+            message: x,
+        }))
+        .collect();
+    cli::print_report(&findings);
+
+    problems.is_empty()
+}
+
+fn check_file<T: ResourceFile>(problems: &mut Vec<String>) -> Option<T> {
+    let file = T::FILE_NAME;
+
+    let raw = match T::load_raw(&T::path()) {
+        Ok(x) => x,
+        Err(e) => {
+            problems.push(format!("{}: unable to read: {}", file, e));
+            return None;
+        }
+    };
+
+    let raw_value: serde_yaml::Value = match serde_yaml::from_str(&raw) {
+        Ok(x) => x,
+        Err(e) => {
+            problems.push(format!("{}: invalid YAML: {}", file, e));
+            return None;
+        }
+    };
+
+    let data = match T::load_from_string(&raw) {
+        Ok(x) => x,
+        Err(e) => {
+            problems.push(format!("{}: invalid data: {}", file, e));
+            return None;
+        }
+    };
+
+    // Anything that doesn't survive a round trip through the data type is an unknown key.
+    let known = serde_yaml::to_value(&data).unwrap();
+    find_unknown_keys(file, "", &raw_value, &known, problems);
+
+    Some(data)
+}
+
+fn find_unknown_keys(
+    file: &str,
+    path: &str,
+    raw: &serde_yaml::Value,
+    known: &serde_yaml::Value,
+    problems: &mut Vec<String>,
+) {
+    use serde_yaml::Value;
+
+    match (raw, known) {
+        (Value::Mapping(raw), Value::Mapping(known)) => {
+            for (key, raw_value) in raw {
+                let key_text = match key {
+                    Value::String(x) => x.clone(),
+                    x => serde_yaml::to_string(x)
+                        .unwrap_or_default()
+                        .trim_start_matches("---")
+                        .trim()
+                        .to_string(),
+                };
+                let path = format!("{}/{}", path, key_text);
+
+                match known.get(key) {
+                    Some(known_value) => find_unknown_keys(file, &path, raw_value, known_value, problems),
+                    None if is_empty(raw_value) => {}
+                    None => problems.push(format!("{}: unknown key: {}", file, path)),
+                }
+            }
+        }
+        (Value::Sequence(raw), Value::Sequence(known)) => {
+            for (i, (raw_value, known_value)) in raw.iter().zip(known).enumerate() {
+                find_unknown_keys(file, &format!("{}/{}", path, i), raw_value, known_value, problems);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Default values are omitted when serializing, so they don't count as unknown.
+fn is_empty(value: &serde_yaml::Value) -> bool {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Null => true,
+        Value::Bool(x) => !x,
+        Value::Mapping(x) => x.is_empty(),
+        Value::Sequence(x) => x.is_empty(),
+        Value::String(_) | Value::Number(_) => false,
+    }
+}
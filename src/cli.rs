@@ -1,10 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use itertools::Itertools;
 
 use crate::{
-    manifest::{placeholder, Manifest, ManifestOverride},
-    schema,
-    steam::SteamCache,
-    wiki::{WikiCache, WikiMetaCache},
+    bootstrap,
+    budget::{self, RunLog},
+    epic::{self, EpicCache},
+    gog,
+    manifest::{placeholder, Manifest, ManifestOverride, Os, RefreshOptions},
+    notify, path, registry,
+    resource::{Autosave, ResourceFile, SaveTarget, AUTOSAVE_MINUTES},
+    schema, should_cancel, should_save,
+    steam::{self, SteamCache},
+    wiki::{self, PathKind, WikiCache, WikiMetaCache},
     Error,
 };
 
@@ -18,9 +26,36 @@ fn styles() -> clap::builder::styling::Styles {
         .placeholder(AnsiColor::Green.on_default())
 }
 
-fn parse_games(games: Vec<String>) -> Vec<String> {
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SoloGame {
+    /// Wiki article title.
+    pub title: String,
+    /// Don't refresh the wiki data.
+    pub skip_wiki: bool,
+    /// Refresh the Steam data even if the wiki data is skipped.
+    pub force_steam: bool,
+}
+
+impl From<String> for SoloGame {
+    fn from(title: String) -> Self {
+        Self {
+            title,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum SoloInput {
+    Title(String),
+    Detailed(SoloGame),
+}
+
+fn parse_games(games: Vec<String>, structured: bool) -> Vec<SoloGame> {
     if !games.is_empty() {
-        games
+        games.into_iter().map(SoloGame::from).collect()
     } else {
         use std::io::IsTerminal;
 
@@ -28,14 +63,106 @@ fn parse_games(games: Vec<String>) -> Vec<String> {
         if stdin.is_terminal() {
             vec![]
         } else {
-            stdin.lines().map_while(Result::ok).collect()
+            match parse_game_list(&std::io::read_to_string(stdin).unwrap_or_default(), structured) {
+                Ok(games) => games,
+                Err(e) => {
+                    eprintln!("Unable to parse game list: {e}");
+                    std::process::exit(2);
+                }
+            }
         }
     }
 }
 
+/// When `structured`, this accepts a JSON array or YAML list, where each item is either a title or a `SoloGame`.
+/// Otherwise, each line is treated as a title, even if it looks like a list.
+fn parse_game_list(raw: &str, structured: bool) -> Result<Vec<SoloGame>, serde_yaml::Error> {
+    if structured {
+        return Ok(serde_yaml::from_str::<Vec<SoloInput>>(raw)?
+            .into_iter()
+            .map(|x| match x {
+                SoloInput::Title(title) => SoloGame::from(title),
+                SoloInput::Detailed(game) => game,
+            })
+            .collect());
+    }
+
+    Ok(raw
+        .lines()
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| SoloGame::from(x.to_string()))
+        .collect())
+}
+
+static FORMAT: std::sync::OnceLock<OutputFormat> = std::sync::OnceLock::new();
+
+/// How to print reports.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal.
+    #[default]
+    Table,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    pub fn select(self) {
+        _ = FORMAT.set(self);
+    }
+
+    pub fn current() -> Self {
+        FORMAT.get().copied().unwrap_or_default()
+    }
+}
+
 #[derive(clap::Parser, Clone, Debug, PartialEq, Eq)]
 #[clap(name = "ludusavi-manifest", version, max_term_width = 100, next_line_help = true, styles = styles())]
 pub struct Cli {
+    /// Use the data files and scripts from this manifest checkout.
+    /// This may also be set with the `LUDUSAVI_MANIFEST_REPO` environment variable.
+    /// Defaults to the source checkout that the program was built from.
+    #[clap(long, global = true)]
+    pub repo: Option<String>,
+
+    /// Send a JSON POST to this URL when a run detects anomalies,
+    /// like a schema failure, fetch failures, or an unusual number of removals.
+    /// This may also be set with the `LUDUSAVI_MANIFEST_WEBHOOK` environment variable.
+    #[clap(long, global = true)]
+    pub webhook: Option<String>,
+
+    /// Keep CBOR copies of the wiki and Steam caches in `tmp/` and load from them
+    /// when the YAML files haven't changed since, which is much faster for local development.
+    /// The YAML files are still the source of truth and are always saved.
+    #[clap(long, global = true)]
+    pub sidecar: bool,
+
+    /// How to print the reports from `check`, `selftest`, `duplicates`, `editions`, `irregular`, `stats`,
+    /// `gog-ids`, `steam-roots`, `overlaps`, `casing`, and `normalize-cache`.
+    /// Progress messages go to stderr, so that the report can be piped.
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// Only save these groups of data files (comma-separated),
+    /// so that CI can fetch and commit each phase of an import separately.
+    /// The Steam and manifest phases are skipped when their files won't be saved.
+    /// Other small caches (e.g., Epic and GOG) are always saved.
+    #[clap(long, global = true, value_enum, value_delimiter = ',')]
+    pub save_only: Vec<SaveTarget>,
+
+    /// Flag an anomaly if the manifest gains more than this percentage of games in one run.
+    #[clap(long, global = true, default_value_t = 5)]
+    pub max_game_growth: usize,
+
+    /// Flag an anomaly if the manifest file grows by more than this percentage in one run.
+    #[clap(long, global = true, default_value_t = 10)]
+    pub max_size_growth: usize,
+
+    /// Don't save the manifest if it grows beyond `--max-game-growth` or `--max-size-growth`,
+    /// and exit with an error instead.
+    #[clap(long, global = true)]
+    pub enforce_growth_limits: bool,
+
     #[clap(subcommand)]
     pub sub: Subcommand,
 }
@@ -61,6 +188,33 @@ pub enum Subcommand {
         #[clap(long)]
         missing_pages: bool,
 
+        /// Load articles from a PCGamingWiki XML dump instead of fetching each one,
+        /// then only fetch the articles that changed after the dump.
+        /// The dump must be from the last 90 days, which is as far back as the wiki's recent changes go.
+        /// New game articles in the dump are added, but articles with `pinRevision` are left alone.
+        #[clap(long)]
+        from_dump: Option<String>,
+
+        /// Check the cached Steam and GOG IDs against the wiki's Cargo tables,
+        /// filling in missing IDs and flagging articles whose IDs changed.
+        #[clap(long)]
+        cargo_ids: bool,
+
+        /// Flag all articles in this wiki category as outdated,
+        /// adding any that aren't cached yet (e.g., "Early access games").
+        #[clap(long)]
+        rescan_category: Option<String>,
+
+        /// Also refresh the redirects for wiki entries
+        /// that haven't been checked in the last 30 days.
+        #[clap(long)]
+        redirects: bool,
+
+        /// Also refresh the interlanguage links for wiki entries,
+        /// for each language configured in `data/wiki-languages.yaml`.
+        #[clap(long)]
+        langlinks: bool,
+
         /// Refresh wiki entries starting from this article title.
         /// This will enable full mode for wiki entries.
         #[clap(long)]
@@ -70,6 +224,41 @@ pub enum Subcommand {
         /// This will enable full mode for Steam entries.
         #[clap(long)]
         steam_from: Option<u32>,
+
+        /// Add Wine prefix equivalents of Windows paths for games on non-Steam stores.
+        #[clap(long)]
+        wine: bool,
+
+        /// Add Proton prefix equivalents of Windows Steam Cloud paths for games without native Linux support.
+        #[clap(long)]
+        proton: bool,
+
+        /// Add the game's Steam DLC IDs to `steamExtra`, up to a limit per game.
+        #[clap(long)]
+        dlc: bool,
+
+        /// Save the wiki cache after refreshing this many entries.
+        #[clap(long, default_value_t = wiki::SAVE_INTERVAL)]
+        wiki_save_interval: u32,
+
+        /// Save the Steam cache after refreshing this many entries.
+        #[clap(long, default_value_t = steam::SAVE_INTERVAL)]
+        steam_save_interval: u32,
+
+        /// Also save the cache being refreshed after this many minutes,
+        /// regardless of how many entries have been refreshed.
+        #[clap(long, default_value_t = AUTOSAVE_MINUTES)]
+        autosave_minutes: u64,
+
+        /// Stop refreshing wiki entries after making this many wiki requests.
+        /// The remaining entries will stay outdated for the next run.
+        #[clap(long)]
+        wiki_budget: Option<usize>,
+
+        /// Stop refreshing Steam entries after fetching this many batches.
+        /// The remaining entries will stay outdated for the next run.
+        #[clap(long)]
+        steam_budget: Option<usize>,
     },
     /// Fetch a named subset of games.
     Solo {
@@ -77,25 +266,131 @@ pub enum Subcommand {
         #[clap(long)]
         local: bool,
 
+        /// Add Wine prefix equivalents of Windows paths for games on non-Steam stores.
+        #[clap(long)]
+        wine: bool,
+
+        /// Add Proton prefix equivalents of Windows Steam Cloud paths for games without native Linux support.
+        #[clap(long)]
+        proton: bool,
+
+        /// Add the game's Steam DLC IDs to `steamExtra`, up to a limit per game.
+        #[clap(long)]
+        dlc: bool,
+
+        /// Read the games from stdin as a JSON/YAML list instead of one title per line
+        /// (e.g., `[{"title": "Celeste", "skipWiki": true, "forceSteam": true}]`).
+        #[clap(long)]
+        structured: bool,
+
         /// Games to update, by wiki article title.
+        /// If not specified, these are read from stdin (see `--structured`).
         #[clap()]
         games: Vec<String>,
     },
+    /// Regenerate the manifest from the cached data without fetching anything,
+    /// then list which games changed.
+    Reprocess {
+        /// Add Wine prefix equivalents of Windows paths for games on non-Steam stores.
+        #[clap(long)]
+        wine: bool,
+
+        /// Add Proton prefix equivalents of Windows Steam Cloud paths for games without native Linux support.
+        #[clap(long)]
+        proton: bool,
+
+        /// Add the game's Steam DLC IDs to `steamExtra`, up to a limit per game.
+        #[clap(long)]
+        dlc: bool,
+    },
     /// Validate the manifest against its schema.
-    Schema,
+    Schema {
+        /// Instead of validating, print type definitions for the manifest format in this language.
+        #[clap(long, value_enum)]
+        types: Option<schema::TypeLanguage>,
+    },
+    /// Validate the data files without any network access.
+    /// If there are problems, print them and exit with 1.
+    Check,
+    /// Run the wiki extraction on a bundled corpus of tricky articles (`data/selftest.yaml`)
+    /// and compare against the expected output, to catch parser regressions.
+    /// If any case fails, print the differences and exit with 1.
+    Selftest,
+    /// Download the published manifest and data caches into this checkout,
+    /// so that you can use `solo` against real data without a full import first.
+    /// Each file is verified before it replaces the local copy.
+    Bootstrap {
+        /// Base URL to download the `data` folder from.
+        #[clap(long, default_value = bootstrap::DEFAULT_SOURCE)]
+        source: String,
+    },
     /// Display some stats about the manifest.
     Stats,
+    /// Time each step of the manifest generation using the local data.
+    Bench,
     /// Find duplicate manifest entries.
     Duplicates,
+    /// Find entries that look like editions of the same game
+    /// and suggest how to consolidate them.
+    Editions,
+    /// Import Epic cloud save info from Legendary's metadata
+    /// for games with an `epicAppName` in the overrides.
+    /// Run `reprocess` afterward to apply it to the manifest.
+    Epic {
+        /// Folder containing Legendary's metadata files.
+        /// Defaults to `metadata` in Legendary's config folder.
+        #[clap(long)]
+        metadata: Option<String>,
+    },
+    /// Check the manifest's GOG IDs against GOG's API
+    /// and list any that don't exist or that point to a pack.
+    /// Results are cached, so only new IDs are looked up by default.
+    GogIds {
+        /// Look up all IDs again, even if they're already cached.
+        #[clap(long)]
+        full: bool,
+    },
     /// List games with irregular paths.
     Irregular,
+    /// Replay the extraction on an article's recent revisions
+    /// and show where the derived paths changed.
+    History {
+        /// How many revisions to check (the wiki allows up to 50 with content).
+        #[clap(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Wiki article title.
+        title: String,
+    },
+    /// List Steam Auto-Cloud roots that we can't map, by how many apps use them.
+    SteamRoots,
+    /// Print the cached Steam data for an app,
+    /// including its launch options and Auto-Cloud info.
+    SteamDump {
+        /// Fetch the latest data from Steam first.
+        #[clap(long)]
+        fetch: bool,
+
+        app_id: u32,
+    },
+    /// Rewrite the wiki cache using the current preprocessing rules,
+    /// dropping obsolete fields and reporting templates that no longer parse.
+    NormalizeCache,
+    /// Find file paths that are claimed by multiple unrelated games,
+    /// which usually indicates a copy-paste error on the wiki.
+    Overlaps,
+    /// Find Linux paths that are inconsistently cased across or within games,
+    /// or that use Windows folder names.
+    /// Linux paths are case-sensitive, so these may not match the real files.
+    Casing,
     /// Try parsing a file containing wikitext.
     /// If there are parsing errors, print them and exit with 1;
     /// otherwise, print nothing and exit with 0.
     Wikitext {
         /// Path to file containing wikitext.
-        #[clap(default_value_t = format!("{}/tmp/wiki.txt", crate::REPO))]
-        path: String,
+        /// Defaults to `tmp/wiki.txt` in the repository.
+        #[clap()]
+        path: Option<String>,
     },
 }
 
@@ -111,6 +406,7 @@ pub async fn run(
     wiki_cache: &mut WikiCache,
     wiki_meta_cache: &mut WikiMetaCache,
     steam_cache: &mut SteamCache,
+    epic_cache: &mut EpicCache,
 ) -> Result<(), Error> {
     match sub {
         Subcommand::Bulk {
@@ -118,64 +414,273 @@ pub async fn run(
             limit,
             recent_changes,
             missing_pages,
+            from_dump,
+            cargo_ids,
+            rescan_category,
+            redirects,
+            langlinks,
             wiki_from,
             steam_from,
+            wine,
+            proton,
+            dlc,
+            wiki_save_interval,
+            steam_save_interval,
+            autosave_minutes,
+            wiki_budget,
+            steam_budget,
         } => {
+            budget::set_limit(budget::Api::Wiki, wiki_budget);
+            budget::set_limit(budget::Api::Steam, steam_budget);
+            let mut run_log = RunLog::new();
+            wiki_cache.purge_removed();
+
             let outdated_only = !full && wiki_from.is_none();
+            if let Some(file) = from_dump {
+                let phase = run_log.start("dump");
+                let dumped = wiki_cache.load_dump(&file, manifest_override)?;
+                run_log.finish(phase);
+
+                // Make sure that we catch up on anything edited after the dump.
+                if wiki_meta_cache.last_checked_recent_changes > dumped {
+                    wiki_meta_cache.last_checked_recent_changes = dumped;
+                }
+                let phase = run_log.start("recent changes");
+                wiki_cache.flag_recent_changes(wiki_meta_cache).await?;
+                run_log.finish(phase);
+            }
             if recent_changes {
+                let phase = run_log.start("recent changes");
                 wiki_cache.flag_recent_changes(wiki_meta_cache).await?;
+                run_log.finish(phase);
             }
             if missing_pages {
+                let phase = run_log.start("missing pages");
                 wiki_cache.add_new_articles().await?;
+                run_log.finish(phase);
+            }
+            if cargo_ids {
+                let phase = run_log.start("cargo ids");
+                wiki_cache.check_cargo_ids().await?;
+                run_log.finish(phase);
+            }
+            if let Some(category) = rescan_category {
+                let phase = run_log.start("rescan category");
+                wiki_cache.rescan_category(&category).await?;
+                run_log.finish(phase);
+            }
+            let phase = run_log.start("wiki");
+            if let Err(e) = wiki_cache
+                .refresh(
+                    outdated_only,
+                    None,
+                    limit,
+                    wiki_from,
+                    manifest_override,
+                    Autosave::new(wiki_save_interval, autosave_minutes),
+                )
+                .await
+            {
+                // If we've been cancelled, we still want to process what we've already fetched.
+                if !should_cancel() {
+                    return Err(e);
+                }
+            }
+            run_log.finish(phase);
+            if redirects {
+                let phase = run_log.start("redirects");
+                if let Err(e) = wiki_cache
+                    .refresh_redirects(None, limit, Autosave::new(wiki_save_interval, autosave_minutes))
+                    .await
+                {
+                    if !should_cancel() {
+                        return Err(e);
+                    }
+                }
+                run_log.finish(phase);
+            }
+            if langlinks {
+                let phase = run_log.start("langlinks");
+                if let Err(e) = wiki_cache
+                    .refresh_langlinks(None, limit, Autosave::new(wiki_save_interval, autosave_minutes))
+                    .await
+                {
+                    if !should_cancel() {
+                        return Err(e);
+                    }
+                }
+                run_log.finish(phase);
             }
-            wiki_cache.refresh(outdated_only, None, limit, wiki_from).await?;
 
-            let outdated_only = steam_from.is_none();
-            steam_cache.transition_states_from(wiki_cache);
-            if let Err(e) = steam_cache.refresh(outdated_only, None, limit, steam_from) {
-                eprintln!("Error: {e:?}");
+            // Marking the wiki entries as handled only sticks if the Steam cache is saved too,
+            // so leave them pending for a later run that does save it.
+            if should_save(SaveTarget::Steam) {
+                let phase = run_log.start("steam");
+                let outdated_only = steam_from.is_none();
+                steam_cache.transition_states_from(wiki_cache, manifest_override);
+                if let Err(e) = steam_cache.refresh(
+                    outdated_only,
+                    None,
+                    limit,
+                    steam_from,
+                    Autosave::new(steam_save_interval, autosave_minutes),
+                ) {
+                    eprintln!("Error: {e:?}");
+                    notify::anomaly(format!("Steam refresh failed: {e}"));
+                }
+                run_log.finish(phase);
             }
 
-            manifest.refresh(manifest_override, wiki_cache, steam_cache)?;
-            schema::validate_manifest(manifest)?;
+            if should_save(SaveTarget::Manifest) {
+                let phase = run_log.start("manifest");
+                manifest.refresh(
+                    manifest_override,
+                    wiki_cache,
+                    steam_cache,
+                    epic_cache,
+                    RefreshOptions { wine, proton, dlc },
+                )?;
+                schema::validate_manifest(manifest)?;
+                run_log.finish(phase);
+            }
+            wiki_meta_cache.last_run = run_log;
 
             if recent_changes {
-                print_stats(manifest, wiki_cache);
+                print_stats(manifest, wiki_cache, wiki_meta_cache);
             }
         }
-        Subcommand::Solo { local, games } => {
-            let games = parse_games(games);
+        Subcommand::Solo {
+            local,
+            wine,
+            proton,
+            dlc,
+            structured,
+            games,
+        } => {
+            let games = parse_games(games, structured);
             let outdated_only = false;
 
             if !local {
-                wiki_cache
-                    .refresh(outdated_only, Some(games.clone()), None, None)
-                    .await?;
+                let titles: Vec<_> = games.iter().filter(|x| !x.skip_wiki).map(|x| x.title.clone()).collect();
+                if let Err(e) = wiki_cache
+                    .refresh(
+                        outdated_only,
+                        Some(titles.clone()),
+                        None,
+                        None,
+                        manifest_override,
+                        Autosave::new(wiki::SAVE_INTERVAL, AUTOSAVE_MINUTES),
+                    )
+                    .await
+                {
+                    // If we've been cancelled, we still want to process what we've already fetched.
+                    if !should_cancel() {
+                        return Err(e);
+                    }
+                }
+                if let Err(e) = wiki_cache
+                    .refresh_redirects(
+                        Some(titles.clone()),
+                        None,
+                        Autosave::new(wiki::SAVE_INTERVAL, AUTOSAVE_MINUTES),
+                    )
+                    .await
+                {
+                    if !should_cancel() {
+                        return Err(e);
+                    }
+                }
+                if let Err(e) = wiki_cache
+                    .refresh_langlinks(Some(titles), None, Autosave::new(wiki::SAVE_INTERVAL, AUTOSAVE_MINUTES))
+                    .await
+                {
+                    if !should_cancel() {
+                        return Err(e);
+                    }
+                }
 
                 let steam_ids: Vec<_> = games
                     .iter()
-                    .filter_map(|x| wiki_cache.0.get(x).and_then(|x| x.steam))
+                    .filter(|x| !x.skip_wiki || x.force_steam)
+                    .flat_map(|x| {
+                        let wiki = wiki_cache.0.get(&x.title);
+                        let primary = wiki.and_then(|x| x.steam);
+                        let side = wiki.map(|x| &x.steam_side);
+                        let co_primary = manifest_override.0.get(&x.title).map(|x| &x.co_primary_steam);
+                        primary
+                            .into_iter()
+                            .chain(side.into_iter().flatten().copied())
+                            .chain(co_primary.into_iter().flatten().copied())
+                    })
                     .collect();
 
-                steam_cache.transition_states_from(wiki_cache);
-                if let Err(e) = steam_cache.refresh(outdated_only, Some(steam_ids), None, None) {
-                    eprintln!("Error: {e:?}");
+                if should_save(SaveTarget::Steam) {
+                    steam_cache.transition_states_from(wiki_cache, manifest_override);
+                    if let Err(e) = steam_cache.refresh(
+                        outdated_only,
+                        Some(steam_ids),
+                        None,
+                        None,
+                        Autosave::new(steam::SAVE_INTERVAL, AUTOSAVE_MINUTES),
+                    ) {
+                        eprintln!("Error: {e:?}");
+                    }
                 }
             }
 
-            manifest.refresh(manifest_override, wiki_cache, steam_cache)?;
+            manifest.refresh(
+                manifest_override,
+                wiki_cache,
+                steam_cache,
+                epic_cache,
+                RefreshOptions { wine, proton, dlc },
+            )?;
             schema::validate_manifest(manifest)?;
         }
-        Subcommand::Schema => {
+        Subcommand::Reprocess { wine, proton, dlc } => {
+            let old = manifest.clone();
+
+            manifest.refresh(
+                manifest_override,
+                wiki_cache,
+                steam_cache,
+                epic_cache,
+                RefreshOptions { wine, proton, dlc },
+            )?;
+            schema::validate_manifest(manifest)?;
+
+            for (title, game) in &manifest.0 {
+                match old.0.get(title) {
+                    None => println!("[+] {}", title),
+                    Some(old_game) if old_game != game => println!("[~] {}", title),
+                    Some(_) => {}
+                }
+            }
+            for title in old.0.keys() {
+                if !manifest.0.contains_key(title) {
+                    println!("[-] {}", title);
+                }
+            }
+        }
+        Subcommand::Schema { .. } => {
             schema::validate_manifest(manifest)?;
         }
+        Subcommand::Check | Subcommand::Selftest | Subcommand::Bootstrap { .. } => {
+            // This is handled before loading the data files.
+        }
         Subcommand::Stats => {
-            print_stats(manifest, wiki_cache);
+            print_stats(manifest, wiki_cache, wiki_meta_cache);
+        }
+        Subcommand::Bench => {
+            bench(manifest_override, wiki_cache, steam_cache, epic_cache)?;
         }
         Subcommand::Duplicates => {
+            #[derive(serde::Serialize)]
+            #[serde(rename_all = "camelCase")]
             struct Duplicate {
-                name: String,
+                group: usize,
                 page_id: u64,
+                name: String,
             }
             let mut data = HashMap::<String, Vec<Duplicate>>::new();
 
@@ -187,29 +692,113 @@ pub async fn run(
                 }
                 let key = serde_json::to_string(info).unwrap();
                 data.entry(key).or_default().push(Duplicate {
-                    name: name.clone(),
+                    group: 0,
                     page_id: wiki_cache.0.get(name).map(|x| x.page_id).unwrap_or(0),
+                    name: name.clone(),
                 });
             }
 
-            for duplicates in data.values() {
-                if duplicates.len() > 1 {
-                    let lines: Vec<_> = duplicates
-                        .iter()
-                        .map(|x| format!("[{}] {}", x.page_id, x.name))
-                        .collect();
-                    println!("\nSame manifest entry:\n  - {}", lines.join("\n  - "));
-                }
-            }
+            // Each group is a set of games with the same manifest entry.
+            let rows: Vec<_> = data
+                .into_values()
+                .filter(|x| x.len() > 1)
+                .sorted_by(|x, y| x[0].name.cmp(&y[0].name))
+                .enumerate()
+                .flat_map(|(group, duplicates)| {
+                    duplicates.into_iter().map(move |x| Duplicate { group: group + 1, ..x })
+                })
+                .collect();
+            print_report(&rows);
+        }
+        Subcommand::Editions => {
+            print_report(&editions(manifest, wiki_cache));
+        }
+        Subcommand::Epic { metadata } => {
+            let app_names = manifest_override
+                .0
+                .values()
+                .filter_map(|x| x.epic_app_name.clone())
+                .collect();
+            let metadata = metadata
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(epic::default_metadata_dir);
+            epic_cache.refresh(&app_names, &metadata);
+        }
+        Subcommand::GogIds { full } => {
+            print_report(&gog::audit(manifest, full)?);
         }
         Subcommand::Irregular => {
-            for (game, info) in &wiki_cache.0 {
-                if info.any_irregular_paths(game.to_string()) {
-                    println!("{}", game);
-                }
+            #[derive(serde::Serialize)]
+            struct Irregular {
+                source: &'static str,
+                name: String,
+                /// Paths as written on the wiki, when we substituted placeholders that should be verified.
+                #[serde(skip_serializing_if = "Vec::is_empty")]
+                substituted: Vec<String>,
             }
+
+            let wiki = wiki_cache
+                .0
+                .iter()
+                .filter(|(game, info)| info.any_irregular_paths(game.to_string()))
+                .map(|(game, info)| Irregular {
+                    source: "wiki",
+                    name: game.clone(),
+                    substituted: info.substituted_paths(game.to_string()),
+                });
+            let steam = steam_cache
+                .0
+                .iter()
+                .filter(|(_, info)| info.irregular)
+                .map(|(app_id, _)| Irregular {
+                    source: "steam",
+                    name: app_id.to_string(),
+                    substituted: vec![],
+                });
+            print_report(&wiki.chain(steam).collect::<Vec<_>>());
+        }
+        Subcommand::History { limit, title } => {
+            history(&title, limit, manifest_override).await?;
+        }
+        Subcommand::SteamRoots => {
+            print_report(&steam::audit_roots(steam_cache));
+        }
+        Subcommand::SteamDump { fetch, app_id } => {
+            if fetch {
+                steam_cache.refresh(
+                    false,
+                    Some(vec![app_id]),
+                    None,
+                    None,
+                    Autosave::new(steam::SAVE_INTERVAL, AUTOSAVE_MINUTES),
+                )?;
+            }
+            match steam_cache.0.get(&app_id) {
+                Some(info) => print!("{}", serde_yaml::to_string(info).unwrap()),
+                None => eprintln!("Steam app {} is not cached", app_id),
+            }
+        }
+        Subcommand::NormalizeCache => {
+            #[derive(serde::Serialize)]
+            struct Unparseable {
+                name: String,
+            }
+
+            let rows: Vec<_> = wiki_cache
+                .normalize()
+                .into_iter()
+                .map(|name| Unparseable { name })
+                .collect();
+            print_report(&rows);
+        }
+        Subcommand::Overlaps => {
+            print_report(&overlaps(manifest, wiki_cache));
+        }
+        Subcommand::Casing => {
+            print_report(&casing(manifest));
         }
         Subcommand::Wikitext { path } => {
+            let path = path.unwrap_or_else(|| format!("{}/tmp/wiki.txt", crate::repo()));
             let Ok(content) = std::fs::read_to_string(&path) else {
                 eprintln!("Unable to read file: {path}");
                 std::process::exit(2);
@@ -232,25 +821,614 @@ pub async fn run(
     Ok(())
 }
 
-fn print_stats(manifest: &Manifest, wiki_cache: &WikiCache) {
-    let games = manifest.0.keys().count();
-    let files_or_registry = manifest
+fn print_stats(manifest: &Manifest, wiki_cache: &WikiCache, wiki_meta_cache: &WikiMetaCache) {
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Stats<'a> {
+        games: usize,
+        games_with_files_or_registry: usize,
+        games_without_files_and_registry: usize,
+        games_in_wiki_cache: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        last_run: Option<&'a RunLog>,
+    }
+
+    let last_run = &wiki_meta_cache.last_run;
+    print_report(&Stats {
+        games: manifest.0.keys().count(),
+        games_with_files_or_registry: manifest
+            .0
+            .values()
+            .filter(|x| !x.files.is_empty() || !x.registry.is_empty())
+            .count(),
+        games_without_files_and_registry: manifest
+            .0
+            .values()
+            .filter(|x| x.files.is_empty() && x.registry.is_empty())
+            .count(),
+        games_in_wiki_cache: wiki_cache.0.keys().count(),
+        last_run: last_run.started.is_some().then_some(last_run),
+    });
+}
+
+/// Print a report in the format from `--format`.
+/// As a table, a list of objects gets one row per item, and an object gets one row per field.
+pub fn print_report<T: serde::Serialize>(report: &T) {
+    match OutputFormat::current() {
+        OutputFormat::Table => {
+            // YAML values keep the field order, which makes for more natural columns.
+            let value = serde_yaml::to_value(report).unwrap();
+            for line in format_table(&table_rows(&value)) {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(report).unwrap()),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(report).unwrap()),
+    }
+}
+
+fn table_rows(value: &serde_yaml::Value) -> Vec<Vec<String>> {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Sequence(items) => {
+            let headers: Vec<String> = items
+                .iter()
+                .filter_map(|x| x.as_mapping())
+                .flat_map(|x| x.iter().map(|(k, _)| table_cell(k)))
+                .unique()
+                .collect();
+            if headers.is_empty() {
+                return items.iter().map(|x| vec![table_cell(x)]).collect();
+            }
+            let rows = items.iter().map(|item| {
+                headers
+                    .iter()
+                    .map(|header| item.get(header).map(table_cell).unwrap_or_default())
+                    .collect()
+            });
+            std::iter::once(headers.clone()).chain(rows).collect()
+        }
+        Value::Mapping(fields) => {
+            let mut out = vec![];
+            for (key, value) in fields {
+                let key = table_cell(key);
+                match value {
+                    Value::Mapping(_) => {
+                        for mut row in table_rows(value) {
+                            row[0] = format!("{}.{}", key, row[0]);
+                            out.push(row);
+                        }
+                    }
+                    Value::Sequence(items) if items.iter().any(|x| x.is_mapping()) => {
+                        for (i, item) in items.iter().enumerate() {
+                            out.push(vec![format!("{}[{}]", key, i), table_cell(item)]);
+                        }
+                    }
+                    _ => out.push(vec![key, table_cell(value)]),
+                }
+            }
+            out
+        }
+        _ => vec![vec![table_cell(value)]],
+    }
+}
+
+fn table_cell(value: &serde_yaml::Value) -> String {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Null => "".to_string(),
+        Value::Bool(x) => x.to_string(),
+        Value::Number(x) => x.to_string(),
+        Value::String(x) => x.clone(),
+        Value::Sequence(items) => items.iter().map(table_cell).join(", "),
+        Value::Mapping(fields) => fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", table_cell(k), table_cell(v)))
+            .join(", "),
+    }
+}
+
+fn format_table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(|x| x.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|x| x.get(i))
+                .map(|x| x.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+async fn history(title: &str, limit: usize, overrides: &ManifestOverride) -> Result<(), Error> {
+    let allow_broad = overrides
         .0
-        .values()
-        .filter(|x| !x.files.is_empty() || !x.registry.is_empty())
-        .count();
-    let no_files_or_registry = manifest
+        .get(title)
+        .map(|x| x.allow_broad.clone())
+        .unwrap_or_default();
+
+    let mut previous: Option<BTreeSet<String>> = None;
+    for revision in wiki::get_revisions(title, limit).await? {
+        let info = wiki::WikiCacheEntry::from_wikitext(&revision.text, title.to_string());
+        let paths: BTreeSet<_> = info
+            .parse_paths(title.to_string(), &allow_broad)
+            .into_iter()
+            .map(|x| x.composite)
+            .collect();
+
+        let (added, removed): (Vec<_>, Vec<_>) = match &previous {
+            None => (paths.iter().collect(), vec![]),
+            Some(previous) => (
+                paths.difference(previous).collect(),
+                previous.difference(&paths).collect(),
+            ),
+        };
+        if previous.is_none() || !added.is_empty() || !removed.is_empty() || info.malformed {
+            println!(
+                "{} | {} | {}{}",
+                revision.id,
+                revision.timestamp,
+                revision.user,
+                if info.malformed { " | malformed" } else { "" }
+            );
+            for path in added {
+                println!("  + {}", path);
+            }
+            for path in removed {
+                println!("  - {}", path);
+            }
+        }
+
+        previous = Some(paths);
+    }
+
+    Ok(())
+}
+
+/// Words that mark a title as a particular edition of some base game.
+const EDITION_WORDS: &[&str] = &[
+    "anniversary",
+    "collector's",
+    "complete",
+    "definitive",
+    "deluxe",
+    "director's cut",
+    "edition",
+    "enhanced",
+    "game of the year",
+    "gold",
+    "goty",
+    "hd",
+    "remastered",
+    "special",
+    "ultimate",
+];
+
+fn edition_base_title(title: &str) -> String {
+    let lower = title.to_lowercase().replace(['™', '®', '©'], "");
+
+    for separator in [": ", " - ", " – "] {
+        if let Some((base, suffix)) = lower.rsplit_once(separator) {
+            if EDITION_WORDS.iter().any(|word| suffix.contains(word)) {
+                return base.trim().to_string();
+            }
+        }
+    }
+
+    let mut base = lower.trim();
+    loop {
+        let before = base;
+        for word in EDITION_WORDS {
+            if let Some(rest) = base.strip_suffix(word) {
+                if rest.ends_with(' ') {
+                    base = rest.trim_end();
+                }
+            }
+        }
+        if base == before {
+            break;
+        }
+    }
+
+    base.to_string()
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Edition {
+    family: usize,
+    page_id: u64,
+    name: String,
+    /// Why the family's titles look like editions of the same game.
+    reasons: Vec<String>,
+    /// What to change in the manifest for this title, if anything.
+    suggestions: Vec<String>,
+}
+
+fn editions(manifest: &Manifest, wiki_cache: &WikiCache) -> Vec<Edition> {
+    let mut by_install_dir = BTreeMap::<String, BTreeSet<&String>>::new();
+    let mut by_gog = BTreeMap::<u64, BTreeSet<&String>>::new();
+    let mut by_title = BTreeMap::<String, BTreeSet<&String>>::new();
+
+    for (title, game) in &manifest.0 {
+        if game.alias.is_some() {
+            continue;
+        }
+        for install_dir in game.install_dir.keys() {
+            by_install_dir
+                .entry(install_dir.to_lowercase())
+                .or_default()
+                .insert(title);
+        }
+        for id in game.gog.id.iter().chain(&game.id.gog_extra) {
+            by_gog.entry(*id).or_default().insert(title);
+        }
+        let base = edition_base_title(title);
+        if !base.is_empty() {
+            by_title.entry(base).or_default().insert(title);
+        }
+    }
+
+    // Merge overlapping groups so that each family is only reported once.
+    let mut families: Vec<(BTreeSet<&String>, BTreeSet<String>)> = vec![];
+    let groups = by_install_dir
+        .into_iter()
+        // Generic folder names like `Game` are shared by many unrelated games.
+        .filter(|(_, v)| v.len() <= 3)
+        .map(|(k, v)| (format!("install dir `{}`", k), v))
+        .chain(by_gog.into_iter().map(|(k, v)| (format!("GOG ID {}", k), v)))
+        .chain(by_title.into_iter().map(|(k, v)| (format!("base title `{}`", k), v)))
+        .filter(|(_, v)| v.len() > 1);
+    for (reason, titles) in groups {
+        let mut members = titles;
+        let mut reasons = BTreeSet::from([reason]);
+        families.retain(|(other_members, other_reasons)| {
+            if members.is_disjoint(other_members) {
+                return true;
+            }
+            members.extend(other_members);
+            reasons.extend(other_reasons.iter().cloned());
+            false
+        });
+        families.push((members, reasons));
+    }
+    families.sort();
+
+    let mut out = vec![];
+    for (family, (members, reasons)) in families.into_iter().enumerate() {
+        // Prefer the entry with the most info, then the shortest title.
+        let primary = *members
+            .iter()
+            .max_by_key(|title| {
+                let game = &manifest.0[**title];
+                (game.files.len() + game.registry.len(), std::cmp::Reverse(title.len()))
+            })
+            .unwrap();
+        let primary_game = &manifest.0[primary];
+
+        let reasons: Vec<_> = reasons.into_iter().collect();
+        let mut steam_extra = BTreeSet::new();
+        let mut gog_extra = BTreeSet::new();
+        let mut rows = vec![];
+        for title in &members {
+            let mut suggestions = vec![];
+            if *title != primary {
+                let game = &manifest.0[*title];
+                if game.files == primary_game.files && game.registry == primary_game.registry {
+                    suggestions.push(format!("make an alias of `{}`", primary));
+                }
+                steam_extra.extend(game.steam.id.filter(|x| !primary_game.id.steam_extra.contains(x)));
+                gog_extra.extend(game.gog.id.filter(|x| !primary_game.id.gog_extra.contains(x)));
+            }
+            rows.push(Edition {
+                family: family + 1,
+                page_id: wiki_cache.0.get(*title).map(|x| x.page_id).unwrap_or(0),
+                name: title.to_string(),
+                reasons: reasons.clone(),
+                suggestions,
+            });
+        }
+
+        if let Some(row) = rows.iter_mut().find(|x| x.name == *primary) {
+            if !steam_extra.is_empty() {
+                row.suggestions
+                    .push(format!("add to `steamExtra`: {}", steam_extra.iter().join(", ")));
+            }
+            if !gog_extra.is_empty() {
+                row.suggestions
+                    .push(format!("add to `gogExtra`: {}", gog_extra.iter().join(", ")));
+            }
+        }
+        out.extend(rows);
+    }
+
+    out
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Overlap {
+    group: usize,
+    page_id: u64,
+    name: String,
+    path: String,
+}
+
+fn overlaps(manifest: &Manifest, wiki_cache: &WikiCache) -> Vec<Overlap> {
+    let mut out = vec![];
+    let mut group = 0;
+
+    let mut paths = BTreeMap::<String, Vec<(&String, &String)>>::new();
+    for (title, game) in &manifest.0 {
+        if game.alias.is_some() {
+            continue;
+        }
+        for path in game.files.keys() {
+            // These are relative to each game's own installation.
+            if path.contains(placeholder::BASE) || path.contains(placeholder::GAME) {
+                continue;
+            }
+            paths.entry(path.to_lowercase()).or_default().push((path, title));
+        }
+    }
+
+    // Games with the same store ID are editions of each other, so they can share paths.
+    let related = |titles: &BTreeSet<&String>| {
+        let games: Vec<_> = titles.iter().map(|x| &manifest.0[*x]).collect();
+        let steam = games[0].steam.id.is_some() && games.iter().all(|x| x.steam.id == games[0].steam.id);
+        let gog = games[0].gog.id.is_some() && games.iter().all(|x| x.gog.id == games[0].gog.id);
+        steam || gog
+    };
+
+    let mut parent: Option<String> = None;
+    for (lower, claims) in &paths {
+        if parent.as_ref().is_some_and(|x| lower.starts_with(&format!("{}/", x))) {
+            continue;
+        }
+
+        // Since the paths are sorted, any nested paths come right after their parent.
+        let nested = format!("{}/", lower);
+        let claims: Vec<_> = claims
+            .iter()
+            .chain(
+                paths
+                    .range(nested.clone()..)
+                    .take_while(|(k, _)| k.starts_with(&nested))
+                    .flat_map(|(_, v)| v),
+            )
+            .collect();
+        let titles: BTreeSet<_> = claims.iter().map(|(_, title)| *title).collect();
+        if titles.len() < 2 || related(&titles) {
+            continue;
+        }
+
+        parent = Some(lower.clone());
+        group += 1;
+        for (path, title) in claims {
+            out.push(Overlap {
+                group,
+                page_id: wiki_cache.0.get(*title).map(|x| x.page_id).unwrap_or(0),
+                name: title.to_string(),
+                path: path.to_string(),
+            });
+        }
+    }
+
+    out
+}
+
+#[derive(serde::Serialize)]
+struct CasingIssue {
+    issue: &'static str,
+    /// Paths in the same group differ only by case.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<usize>,
+    name: String,
+    path: String,
+}
+
+fn casing(manifest: &Manifest) -> Vec<CasingIssue> {
+    // Wine and Proton prefixes legitimately use Windows folder names.
+    const PREFIX: &str = "/drive_c/";
+    const WINDOWS_FOLDERS: &[&str] = &[
+        "AppData",
+        "Application Data",
+        "Local Settings",
+        "LocalLow",
+        "My Documents",
+        "Saved Games",
+    ];
+
+    let mut out = vec![];
+    let mut paths = BTreeMap::<(Option<&String>, String), BTreeSet<(&String, &String)>>::new();
+    for (title, game) in &manifest.0 {
+        for (path, entry) in &game.files {
+            if !entry.when.iter().any(|x| x.os == Some(Os::Linux)) || path.contains(PREFIX) {
+                continue;
+            }
+
+            // These are relative to each game's own installation, so we only compare them within the game.
+            let scope = (path.contains(placeholder::BASE) || path.contains(placeholder::GAME)).then_some(title);
+            paths
+                .entry((scope, path.to_lowercase()))
+                .or_default()
+                .insert((path, title));
+
+            let windows_folders: Vec<_> = path
+                .split('/')
+                .filter(|segment| {
+                    segment.starts_with("<win") || WINDOWS_FOLDERS.iter().any(|x| x.eq_ignore_ascii_case(segment))
+                })
+                .collect();
+            if !windows_folders.is_empty() {
+                out.push(CasingIssue {
+                    issue: "windows folder",
+                    group: None,
+                    name: title.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+    }
+
+    let mut group = 0;
+    for claims in paths.values() {
+        let variants: BTreeSet<_> = claims.iter().map(|(path, _)| *path).collect();
+        if variants.len() < 2 {
+            continue;
+        }
+
+        group += 1;
+        for (path, title) in claims {
+            out.push(CasingIssue {
+                issue: "inconsistent casing",
+                group: Some(group),
+                name: title.to_string(),
+                path: path.to_string(),
+            });
+        }
+    }
+
+    out
+}
+
+fn bench(
+    manifest_override: &ManifestOverride,
+    wiki_cache: &WikiCache,
+    steam_cache: &SteamCache,
+    epic_cache: &EpicCache,
+) -> Result<(), Error> {
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let paths: Vec<_> = wiki_cache
         .0
-        .values()
-        .filter(|x| x.files.is_empty() && x.registry.is_empty())
-        .count();
-    let in_wiki_cache = wiki_cache.0.keys().count();
-
-    println!("Total games in manifest: {}", games);
-    println!("Total games in manifest with files or registry: {}", files_or_registry);
-    println!(
-        "Total games in manifest without files and registry: {}",
-        no_files_or_registry
-    );
-    println!("Total games in wiki cache: {}", in_wiki_cache);
+        .iter()
+        .flat_map(|(title, info)| info.parse_paths(title.to_string(), &Default::default()))
+        .collect();
+    let parsing = start.elapsed();
+
+    let start = Instant::now();
+    for path in &paths {
+        match path.kind {
+            None | Some(PathKind::File) => {
+                path::normalize(&path.composite);
+            }
+            Some(PathKind::Registry) => {
+                registry::normalize(&path.composite);
+            }
+        }
+    }
+    let normalization = start.elapsed();
+
+    let start = Instant::now();
+    let mut manifest = Manifest::default();
+    manifest.refresh(
+        manifest_override,
+        wiki_cache,
+        steam_cache,
+        epic_cache,
+        RefreshOptions::default(),
+    )?;
+    let assembly = start.elapsed();
+
+    let start = Instant::now();
+    let serialized = manifest.serialize();
+    let serialization = start.elapsed();
+
+    let start = Instant::now();
+    schema::validate_manifest(&manifest)?;
+    let validation = start.elapsed();
+
+    println!("Wiki template parsing ({} paths): {:?}", paths.len(), parsing);
+    println!("Path normalization: {:?}", normalization);
+    println!("Manifest assembly ({} games): {:?}", manifest.0.len(), assembly);
+    println!("YAML serialization ({} bytes): {:?}", serialized.len(), serialization);
+    println!("Schema validation: {:?}", validation);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_format_reports_as_tables() {
+        #[derive(serde::Serialize)]
+        struct Row {
+            name: &'static str,
+            count: usize,
+        }
+
+        let rows = table_rows(
+            &serde_yaml::to_value(vec![Row { name: "a", count: 10 }, Row { name: "bbb", count: 2 }]).unwrap(),
+        );
+        assert_eq!(vec!["name  count", "a     10", "bbb   2"], format_table(&rows));
+
+        let fields =
+            table_rows(&serde_yaml::from_str("{games: 1, lastRun: {started: x, phases: [{name: wiki}]}}").unwrap());
+        assert_eq!(
+            vec![
+                "games              1",
+                "lastRun.started    x",
+                "lastRun.phases[0]  name=wiki"
+            ],
+            format_table(&fields)
+        );
+    }
+
+    #[test]
+    fn can_find_edition_base_title() {
+        assert_eq!("celeste", edition_base_title("Celeste"));
+        assert_eq!("skyrim", edition_base_title("Skyrim Special Edition"));
+        assert_eq!("fallout 3", edition_base_title("Fallout 3: Game of the Year Edition"));
+        assert_eq!("gold", edition_base_title("Gold"));
+        assert_eq!("marigold", edition_base_title("Marigold"));
+    }
+
+    #[test]
+    fn can_parse_game_list() {
+        assert_eq!(
+            vec![
+                SoloGame::from("Celeste".to_string()),
+                SoloGame::from("Hades".to_string())
+            ],
+            parse_game_list("Celeste\nHades\n", false).unwrap(),
+        );
+        assert_eq!(
+            vec![SoloGame::from("[Foo]".to_string()), SoloGame::from("- Bar".to_string())],
+            parse_game_list("[Foo]\n- Bar\n", false).unwrap(),
+        );
+        assert_eq!(
+            vec![
+                SoloGame::from("Celeste".to_string()),
+                SoloGame {
+                    title: "Hades".to_string(),
+                    skip_wiki: true,
+                    force_steam: true,
+                },
+            ],
+            parse_game_list(
+                r#"["Celeste", {"title": "Hades", "skipWiki": true, "forceSteam": true}]"#,
+                true
+            )
+            .unwrap(),
+        );
+        assert!(parse_game_list("Celeste: {", true).is_err());
+    }
 }
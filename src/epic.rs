@@ -0,0 +1,151 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    manifest::{placeholder, Os},
+    resource::ResourceFile,
+    should_cancel,
+};
+
+/// Epic cloud save info, keyed by Legendary's app name for each game.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct EpicCache(pub BTreeMap<String, EpicCacheEntry>);
+
+impl ResourceFile for EpicCache {
+    const FILE_NAME: &'static str = "data/epic-game-cache.yaml";
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EpicCacheEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud_save_folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud_save_folder_mac: Option<String>,
+}
+
+impl EpicCache {
+    /// Import the cloud save info from Legendary's metadata files,
+    /// which it downloads from the Epic catalog after logging in.
+    pub fn refresh(&mut self, app_names: &BTreeSet<String>, metadata_dir: &Path) {
+        for app_name in app_names {
+            if should_cancel() {
+                break;
+            }
+
+            let file = metadata_dir.join(format!("{}.json", app_name));
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                eprintln!("Epic: {app_name} - no metadata at {}", file.display());
+                continue;
+            };
+            let info = match serde_json::from_str::<legendary::Game>(&content) {
+                Ok(x) => x,
+                Err(e) => {
+                    eprintln!("Epic: {app_name} - unable to parse metadata: {e}");
+                    continue;
+                }
+            };
+
+            let attributes = info.metadata.custom_attributes;
+            let value = |key: &str| {
+                attributes
+                    .get(key)
+                    .map(|x| x.value.trim().to_string())
+                    .filter(|x| !x.is_empty())
+            };
+
+            self.0.insert(
+                app_name.to_string(),
+                EpicCacheEntry {
+                    cloud_save_folder: value("CloudSaveFolder"),
+                    cloud_save_folder_mac: value("CloudSaveFolder_MAC"),
+                },
+            );
+        }
+    }
+}
+
+/// Legendary's default location for its metadata files.
+pub fn default_metadata_dir() -> PathBuf {
+    let config = std::env::var("LEGENDARY_CONFIG_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .unwrap_or_default();
+            PathBuf::from(home).join(".config/legendary")
+        });
+    config.join("metadata")
+}
+
+/// Convert Epic's path variables into manifest placeholders.
+/// Returns None if the path uses a variable that we don't know how to map.
+pub fn parse_path(path: &str, os: Os) -> Option<String> {
+    static VARIABLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}]+)\}").unwrap());
+
+    let mut unknown = false;
+    let out = VARIABLE.replace_all(path, |captures: &regex::Captures| {
+        let replacement = match (captures[1].to_lowercase().as_str(), os) {
+            ("appdata", Os::Mac) => "<home>/Library/Application Support",
+            ("appdata", _) => placeholder::WIN_LOCAL_APP_DATA,
+            ("epicid", _) => placeholder::STORE_USER_ID,
+            ("installdir", _) => placeholder::BASE,
+            ("userdir", Os::Mac) => "<home>/Documents",
+            ("userdir", _) => placeholder::WIN_DOCUMENTS,
+            ("userprofile" | "home", _) => placeholder::HOME,
+            ("usersavedgames", Os::Windows) => "<home>/Saved Games",
+            _ => {
+                unknown = true;
+                ""
+            }
+        };
+        replacement.to_string()
+    });
+
+    (!unknown).then(|| out.to_string())
+}
+
+mod legendary {
+    use super::*;
+
+    #[derive(Debug, Default, Clone, serde::Deserialize)]
+    #[serde(default)]
+    pub struct Game {
+        pub metadata: Metadata,
+    }
+
+    #[derive(Debug, Default, Clone, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct Metadata {
+        pub custom_attributes: BTreeMap<String, CustomAttribute>,
+    }
+
+    #[derive(Debug, Default, Clone, serde::Deserialize)]
+    #[serde(default)]
+    pub struct CustomAttribute {
+        pub value: String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_path() {
+        assert_eq!(
+            Some("<winLocalAppData>/Game/Saved/SaveGames".to_string()),
+            parse_path("{AppData}/Game/Saved/SaveGames", Os::Windows),
+        );
+        assert_eq!(
+            Some("<home>/Library/Application Support/Game/<storeUserId>".to_string()),
+            parse_path("{AppData}/Game/{EpicId}", Os::Mac),
+        );
+        assert_eq!(None, parse_path("{Unknown}/Game", Os::Windows));
+    }
+}
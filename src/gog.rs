@@ -0,0 +1,154 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    process::Command,
+};
+
+use itertools::Itertools;
+
+use crate::{find_decoding_culprit, manifest::Manifest, repo, resource::ResourceFile, should_cancel, Error};
+
+const CHUNK_SIZE: usize = 25;
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct GogCache(pub BTreeMap<u64, GogCacheEntry>);
+
+impl ResourceFile for GogCache {
+    const FILE_NAME: &'static str = "data/gog-game-cache.yaml";
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GogCacheEntry {
+    /// GOG's API does not know about this product.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl GogCacheEntry {
+    pub fn is_pack(&self) -> bool {
+        self.game_type.as_deref() == Some("pack")
+    }
+}
+
+impl GogCache {
+    /// Look up any product IDs that aren't cached yet, or all of them if `full` is set.
+    pub fn refresh(&mut self, product_ids: &BTreeSet<u64>, full: bool) -> Result<(), Error> {
+        let product_ids: Vec<_> = product_ids
+            .iter()
+            .filter(|x| full || !self.0.contains_key(x))
+            .copied()
+            .collect();
+
+        for product_ids in product_ids.chunks(CHUNK_SIZE) {
+            if should_cancel() {
+                break;
+            }
+
+            let info = fetch(product_ids)?;
+            for product_id in product_ids {
+                let entry = match info.get(&product_id.to_string()) {
+                    Some(Some(product)) => GogCacheEntry {
+                        missing: false,
+                        game_type: product.game_type.clone(),
+                        title: product.title.clone(),
+                    },
+                    Some(None) => GogCacheEntry {
+                        missing: true,
+                        ..Default::default()
+                    },
+                    None => continue,
+                };
+                self.0.insert(*product_id, entry);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+struct Product {
+    game_type: Option<String>,
+    title: Option<String>,
+}
+
+fn fetch(product_ids: &[u64]) -> Result<BTreeMap<String, Option<Product>>, Error> {
+    // This runs while building the `gog-ids` report, so it mustn't mix with the report on stdout.
+    eprintln!("GOG batch: {}", product_ids.iter().join(", "));
+
+    let mut cmd = Command::new("python");
+    cmd.arg(format!("{}/scripts/get-gog-product-info.py", repo()));
+    for product_id in product_ids {
+        cmd.arg(product_id.to_string());
+    }
+
+    let output = cmd.output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eprintln!("GOG product info failure: {}", &stderr);
+        return Err(Error::GogProductInfo);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    serde_json::from_str(&stdout).map_err(|e| {
+        let culprit = serde_json::from_str::<serde_json::Value>(&stdout)
+            .ok()
+            .and_then(|raw| find_decoding_culprit::<Option<Product>>(&raw))
+            .and_then(|(product_id, e)| Some((product_id.parse::<u64>().ok()?, e)));
+        match culprit {
+            Some((product_id, e)) => Error::GogProductInfoDecoding(e).for_gog_product(product_id),
+            None => Error::GogProductInfoDecoding(e),
+        }
+    })
+}
+
+/// Check each GOG ID in the manifest and report any that GOG doesn't know about
+/// (usually typos on the wiki) or that point to a pack instead of a game.
+pub fn audit(manifest: &Manifest, full: bool) -> Result<Vec<AuditFinding>, Error> {
+    let mut usages = BTreeMap::<u64, Vec<String>>::new();
+    for (title, game) in &manifest.0 {
+        if let Some(id) = game.gog.id {
+            usages.entry(id).or_default().push(format!("{} (gog.id)", title));
+        }
+        for id in &game.id.gog_extra {
+            usages.entry(*id).or_default().push(format!("{} (gogExtra)", title));
+        }
+    }
+
+    let mut cache = GogCache::load().unwrap();
+    let result = cache.refresh(&usages.keys().copied().collect(), full);
+    cache.save();
+    result?;
+
+    let mut findings = vec![];
+    for (id, titles) in usages {
+        let Some(info) = cache.0.get(&id) else {
+            continue;
+        };
+        let problem = if info.missing {
+            "not found"
+        } else if info.is_pack() {
+            "pack"
+        } else {
+            continue;
+        };
+        for title in titles {
+            findings.push(AuditFinding { problem, id, title });
+        }
+    }
+
+    Ok(findings)
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AuditFinding {
+    pub problem: &'static str,
+    pub id: u64,
+    /// Manifest title and which field has the ID.
+    pub title: String,
+}
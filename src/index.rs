@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    gog::GogCache,
+    manifest::Manifest,
+    resource::{ResourceFile, SaveTarget},
+    steam::SteamCache,
+};
+
+/// Steam's localized store names, keyed by the canonical manifest title,
+/// so that tools can match a user's non-English library titles to manifest entries.
+/// English names are left out, since those are what the canonical titles are based on.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct LocalizedNames(pub BTreeMap<String, BTreeMap<String, String>>);
+
+impl ResourceFile for LocalizedNames {
+    const FILE_NAME: &'static str = "data/localized-names.yaml";
+    const SAVE_TARGET: Option<SaveTarget> = Some(SaveTarget::Manifest);
+}
+
+impl LocalizedNames {
+    pub fn refresh(&mut self, manifest: &Manifest, steam_cache: &SteamCache) {
+        self.0.clear();
+
+        for (title, game) in &manifest.0 {
+            if game.alias.is_some() {
+                continue;
+            }
+            let Some(id) = game.steam.id else { continue };
+            let Some(info) = steam_cache.0.get(&id) else { continue };
+
+            let names: BTreeMap<_, _> = info
+                .name_localized
+                .iter()
+                .filter(|(language, name)| {
+                    language.as_str() != "english" && !name.trim().is_empty() && name.as_str() != title
+                })
+                .map(|(language, name)| (language.clone(), name.trim().to_string()))
+                .collect();
+
+            if !names.is_empty() {
+                self.0.insert(title.clone(), names);
+            }
+        }
+    }
+}
+
+pub fn save_localized_names(manifest: &Manifest, steam_cache: &SteamCache) {
+    let mut names = LocalizedNames::default();
+    names.refresh(manifest, steam_cache);
+    names.save();
+}
+
+/// Store names for each game's side IDs (`steamExtra` and `gogExtra`),
+/// so that maintainers can verify that they belong to the listed game.
+/// Unresolved IDs are kept with no name.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SideIdTitles(pub BTreeMap<String, SideIds>);
+
+impl ResourceFile for SideIdTitles {
+    const FILE_NAME: &'static str = "data/side-id-titles.yaml";
+    const SAVE_TARGET: Option<SaveTarget> = Some(SaveTarget::Manifest);
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SideIds {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub steam: BTreeMap<u32, Option<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub gog: BTreeMap<u64, Option<String>>,
+}
+
+impl SideIdTitles {
+    pub fn refresh(&mut self, manifest: &Manifest, steam_cache: &SteamCache, gog_cache: &GogCache) {
+        self.0.clear();
+
+        for (title, game) in &manifest.0 {
+            if game.alias.is_some() || (game.id.steam_extra.is_empty() && game.id.gog_extra.is_empty()) {
+                continue;
+            }
+
+            let ids = SideIds {
+                steam: game
+                    .id
+                    .steam_extra
+                    .iter()
+                    .map(|id| (*id, steam_cache.0.get(id).and_then(|x| x.name.clone())))
+                    .collect(),
+                gog: game
+                    .id
+                    .gog_extra
+                    .iter()
+                    .map(|id| (*id, gog_cache.0.get(id).and_then(|x| x.title.clone())))
+                    .collect(),
+            };
+
+            self.0.insert(title.clone(), ids);
+        }
+    }
+}
+
+pub fn save_side_id_titles(manifest: &Manifest, steam_cache: &SteamCache) {
+    let Ok(gog_cache) = GogCache::load() else {
+        return;
+    };
+    let mut titles = SideIdTitles::default();
+    titles.refresh(manifest, steam_cache, &gog_cache);
+    titles.save();
+}
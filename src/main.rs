@@ -1,10 +1,19 @@
+mod bootstrap;
+mod budget;
+mod check;
 mod cli;
+mod epic;
+mod gog;
+mod index;
 mod manifest;
 mod missing;
+mod notify;
 mod path;
+mod quality;
 mod registry;
 mod resource;
 mod schema;
+mod selftest;
 mod steam;
 mod wiki;
 
@@ -13,17 +22,35 @@ use std::sync::{
     Arc,
 };
 
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 
 use crate::{
+    epic::EpicCache,
     manifest::{Manifest, ManifestOverride},
-    resource::ResourceFile,
+    resource::{ResourceFile, SaveTarget},
     steam::SteamCache,
     wiki::{WikiCache, WikiMetaCache},
 };
 
-pub const REPO: &str = env!("CARGO_MANIFEST_DIR");
+static REPO: OnceCell<String> = OnceCell::new();
 static CANCEL: Lazy<Arc<AtomicBool>> = Lazy::new(|| Arc::new(AtomicBool::new(false)));
+static SIDECAR: AtomicBool = AtomicBool::new(false);
+static SAVE_ONLY: OnceCell<Vec<SaveTarget>> = OnceCell::new();
+
+/// The manifest checkout containing the data files and scripts.
+pub fn repo() -> &'static str {
+    REPO.get().map(|x| x.as_str()).unwrap_or(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Whether to keep binary copies of the big caches for faster loading.
+pub fn use_sidecar() -> bool {
+    SIDECAR.load(Ordering::Relaxed)
+}
+
+/// Whether this group of data files should be saved, per `--save-only`.
+pub fn should_save(target: SaveTarget) -> bool {
+    SAVE_ONLY.get().is_none_or(|x| x.is_empty() || x.contains(&target))
+}
 
 pub fn should_cancel() -> bool {
     CANCEL.load(Ordering::Relaxed)
@@ -80,13 +107,79 @@ pub enum Error {
     SteamProductInfo,
     #[error("Could not decode product info: {0:?}")]
     SteamProductInfoDecoding(serde_json::Error),
+    #[error("Could not find GOG product info")]
+    GogProductInfo,
+    #[error("Could not decode GOG product info: {0:?}")]
+    GogProductInfoDecoding(serde_json::Error),
+    #[error("Could not read wiki dump")]
+    WikiDump,
+    #[error("Could not decode wiki dump: {0:?}")]
+    WikiDumpDecoding(serde_json::Error),
+    #[error("Wiki dump from {0} is older than the wiki's recent changes, so later edits would be lost")]
+    WikiDumpTooOld(chrono::DateTime<chrono::Utc>),
     #[error("Schema validation failed for manifest")]
     ManifestSchema,
+    #[error("Could not load {0}: {1}")]
+    DataFile(&'static str, String),
     #[error("Subprocess: {0}")]
     Subprocess(#[from] std::io::Error),
+    #[error("{title} (page {page_id}): {source}")]
+    WikiPage {
+        title: String,
+        page_id: u64,
+        source: Box<Error>,
+    },
+    #[error("Steam app {app_id}: {source}")]
+    SteamApp { app_id: u32, source: Box<Error> },
+    #[error("GOG product {product_id}: {source}")]
+    GogProduct { product_id: u64, source: Box<Error> },
 }
 
 impl Error {
+    /// Note which wiki article was being processed, unless the error already names one.
+    pub fn for_wiki_page(self, title: &str, page_id: u64) -> Self {
+        match self {
+            Self::WikiPage { .. } => self,
+            _ => Self::WikiPage {
+                title: title.to_string(),
+                page_id,
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// Note which Steam app was being processed, unless the error already names one.
+    pub fn for_steam_app(self, app_id: u32) -> Self {
+        match self {
+            Self::SteamApp { .. } => self,
+            _ => Self::SteamApp {
+                app_id,
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// Note which GOG product was being processed, unless the error already names one.
+    pub fn for_gog_product(self, product_id: u64) -> Self {
+        match self {
+            Self::GogProduct { .. } => self,
+            _ => Self::GogProduct {
+                product_id,
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// The underlying error, without the context of where it happened.
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::WikiPage { source, .. } | Self::SteamApp { source, .. } | Self::GogProduct { source, .. } => {
+                source.root()
+            }
+            _ => self,
+        }
+    }
+
     pub fn should_discard_work(&self) -> bool {
         match self {
             Error::WikiClient(_)
@@ -94,26 +187,115 @@ impl Error {
             | Error::PageMissing
             | Error::SteamProductInfo
             | Error::SteamProductInfoDecoding(_)
+            | Error::GogProductInfo
+            | Error::GogProductInfoDecoding(_)
+            | Error::WikiDump
+            | Error::WikiDumpDecoding(_)
+            | Error::WikiDumpTooOld(_)
+            | Error::DataFile(..)
             | Error::Subprocess(_) => false,
             Error::ManifestSchema => true,
+            Error::WikiPage { source, .. } | Error::SteamApp { source, .. } | Error::GogProduct { source, .. } => {
+                source.should_discard_work()
+            }
         }
     }
+
+    /// A best guess at what went wrong, for the failure report.
+    pub fn suspected_cause(&self) -> &'static str {
+        match self {
+            Error::WikiClient(_) => "PCGamingWiki was unreachable or rejected the request, which is usually temporary",
+            Error::WikiData(_) => "the wiki API response changed shape or was truncated",
+            Error::PageMissing => "the article was deleted or moved without a redirect",
+            Error::SteamProductInfo => "Steam returned no product info, which is usually temporary",
+            Error::SteamProductInfoDecoding(_) => "Steam's product info format changed",
+            Error::GogProductInfo => "GOG returned no product info, which is usually temporary",
+            Error::GogProductInfoDecoding(_) => "GOG's product info format changed",
+            Error::WikiDump | Error::WikiDumpDecoding(_) => "the wiki dump is incomplete or in an unexpected format",
+            Error::WikiDumpTooOld(_) => "the wiki dump needs to be replaced with a newer one",
+            Error::ManifestSchema => "the generated manifest no longer matches the schema, likely from a parser change",
+            Error::DataFile(..) => "a data file is missing or malformed, which `check` can explain",
+            Error::Subprocess(_) => "a helper script could not run, so check the Python dependencies",
+            Error::WikiPage { source, .. } | Error::SteamApp { source, .. } | Error::GogProduct { source, .. } => {
+                source.suspected_cause()
+            }
+        }
+    }
+}
+
+/// When a batch response can't be decoded, find the first entry that fails on its own,
+/// so that the error can point to it instead of the whole batch.
+pub fn find_decoding_culprit<T: serde::de::DeserializeOwned>(
+    entries: &serde_json::Value,
+) -> Option<(String, serde_json::Error)> {
+    entries.as_object()?.iter().find_map(|(key, value)| {
+        serde_json::from_value::<T>(value.clone())
+            .err()
+            .map(|e| (key.clone(), e))
+    })
 }
 
 #[tokio::main]
 async fn main() {
     let cli = cli::parse();
 
+    if let Some(repo) = cli
+        .repo
+        .clone()
+        .or_else(|| std::env::var("LUDUSAVI_MANIFEST_REPO").ok())
+    {
+        REPO.set(repo).unwrap();
+    }
+    SIDECAR.store(cli.sidecar, Ordering::Relaxed);
+    SAVE_ONLY.set(cli.save_only.clone()).unwrap();
+    cli.format.select();
+
+    if cli.sub == cli::Subcommand::Check {
+        // This needs to work even if the data files can't be loaded,
+        // and it shouldn't modify anything.
+        if !check::check_data_files() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let cli::Subcommand::Bootstrap { source } = &cli.sub {
+        // The local data files may be missing or outdated, so this replaces them before anything loads them.
+        if !bootstrap::run(source) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if cli.sub == cli::Subcommand::Selftest {
+        // This only depends on the corpus and the parsing rules.
+        if !selftest::run() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let cli::Subcommand::Schema { types: Some(language) } = cli.sub {
+        // This only depends on the schema, so we don't need to load or save anything else.
+        print!("{}", schema::generate_types(language));
+        return;
+    }
+
     signal_hook::flag::register(signal_hook::consts::SIGINT, (*CANCEL).clone()).unwrap();
 
     let mut wiki_cache = WikiCache::load().unwrap();
     let mut wiki_meta_cache = WikiMetaCache::load().unwrap();
     let mut steam_cache = SteamCache::load().unwrap();
+    let mut epic_cache = EpicCache::load().unwrap();
     let mut manifest = Manifest::load().unwrap();
     let mut manifest_override = ManifestOverride::load().unwrap();
+    let original_manifest = manifest.clone();
 
+    let bulk = matches!(cli.sub, cli::Subcommand::Bulk { .. });
+    let full = bulk || matches!(cli.sub, cli::Subcommand::Reprocess { .. });
     let mut success = true;
     let mut discard = false;
+    let mut run_failure = None;
     if let Err(e) = cli::run(
         cli.sub,
         &mut manifest,
@@ -121,12 +303,36 @@ async fn main() {
         &mut wiki_cache,
         &mut wiki_meta_cache,
         &mut steam_cache,
+        &mut epic_cache,
     )
     .await
     {
         eprintln!("{e}");
+        if !should_cancel() {
+            notify::anomaly(format!("Run failed: {e}"));
+        }
         success = false;
         discard = e.should_discard_work();
+        if !should_cancel() {
+            run_failure = Some(notify::Failure::new(&e));
+        }
+    }
+
+    if bulk {
+        notify::save_failure_report(run_failure);
+    }
+
+    if should_cancel() && !discard {
+        eprintln!("Cancelled; saving partial progress");
+    }
+
+    // This compares against the saved manifest, so it has to happen before saving.
+    let within_limits =
+        notify::check_manifest_growth(&original_manifest, &manifest, cli.max_game_growth, cli.max_size_growth);
+    if !within_limits && cli.enforce_growth_limits {
+        eprintln!("Manifest grew beyond the limits, so it will not be saved");
+        manifest = original_manifest.clone();
+        success = false;
     }
 
     if !discard {
@@ -135,9 +341,42 @@ async fn main() {
         }
         wiki_cache.save();
         steam_cache.save();
+        epic_cache.save();
         manifest.save();
-        missing::save_missing_games(&wiki_cache, &manifest, &manifest_override);
-        wiki::save_malformed_list(&wiki_cache);
+        schema::ManifestMeta::current().save();
+        notify::check_manifest_changes(&original_manifest, &manifest);
+        // Parsing every path on the wiki is slow, so the reports that need it share one pass,
+        // and they're only refreshed by the commands that process everything.
+        let wiki_paths = (full && (should_save(SaveTarget::Manifest) || should_save(SaveTarget::Wiki)))
+            .then(|| wiki_cache.parse_all_paths());
+        if should_save(SaveTarget::Manifest) {
+            manifest::save_badges(&manifest);
+            manifest::save_typo_aliases_list(&manifest, &wiki_cache);
+            missing::save_missing_games(&wiki_cache, &manifest, &manifest_override);
+            missing::save_removed_games(&original_manifest, &manifest, &wiki_cache);
+            index::save_localized_names(&manifest, &steam_cache);
+            index::save_side_id_titles(&manifest, &steam_cache);
+            if let Some(wiki_paths) = &wiki_paths {
+                quality::save_quality_report(&manifest, &manifest_override, &wiki_cache, wiki_paths, &steam_cache);
+            }
+        }
+        if should_save(SaveTarget::Wiki) {
+            wiki::save_malformed_list(&wiki_cache);
+            wiki::save_not_game_list(&wiki_cache);
+            if let Some(wiki_paths) = &wiki_paths {
+                wiki::save_too_broad_list(&wiki_cache, wiki_paths, &manifest_override);
+                wiki::save_omitted_registry_list(&wiki_cache, wiki_paths, &manifest_override);
+                manifest::save_merged_rows_list(&wiki_cache, wiki_paths, &manifest_override);
+            }
+        }
+    }
+
+    if let Some(webhook) = cli
+        .webhook
+        .clone()
+        .or_else(|| std::env::var("LUDUSAVI_MANIFEST_WEBHOOK").ok())
+    {
+        notify::send(&webhook);
     }
 
     if !success {
@@ -1,19 +1,43 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+
+use itertools::Itertools;
 
 use crate::{
+    epic::{self, EpicCache, EpicCacheEntry},
     path,
-    resource::ResourceFile,
-    steam::{self, SteamCache, SteamCacheEntry},
-    wiki::{PathKind, PrimaryIds, WikiCache, WikiCacheEntry},
+    resource::{ResourceFile, SaveTarget, YamlStyle},
+    steam::{self, LaunchKind, SteamCache, SteamCacheEntry},
+    wiki::{PathKind, PrimaryIds, WikiCache, WikiCacheEntry, WikiPath},
     Error,
 };
 
+/// Some games have hundreds of DLC (e.g., soundtracks and cosmetics),
+/// so we only add this many IDs per game.
+pub const MAX_DLC: usize = 50;
+
+/// Old titles scoring below this (out of 100) are not added as aliases.
+const MIN_ALIAS_SCORE: u8 = 50;
+
+/// Wiki namespaces that can show up in page moves, but never for a real game title.
+const NON_GAME_NAMESPACES: &[&str] = &[
+    "category",
+    "draft",
+    "file",
+    "help",
+    "pcgamingwiki",
+    "talk",
+    "template",
+    "user",
+    "user talk",
+];
+
 pub mod placeholder {
     pub const ALL: &[&str] = &[
         ROOT,
         GAME,
         BASE,
         HOME,
+        STORE_GAME_ID,
         STORE_USER_ID,
         OS_USER_NAME,
         WIN_APP_DATA,
@@ -44,6 +68,7 @@ pub mod placeholder {
     pub const GAME: &str = "<game>";
     pub const BASE: &str = "<base>";
     pub const HOME: &str = "<home>";
+    pub const STORE_GAME_ID: &str = "<storeGameId>";
     pub const STORE_USER_ID: &str = "<storeUserId>";
     pub const OS_USER_NAME: &str = "<osUserName>";
     pub const WIN_APP_DATA: &str = "<winAppData>";
@@ -56,19 +81,287 @@ pub mod placeholder {
     pub const XDG_CONFIG: &str = "<xdgConfig>";
 }
 
-fn do_launch_paths_match(from_steam: Option<String>, from_manifest: Option<String>) -> bool {
+/// Reduce a title to its lowercase letters and digits,
+/// so that titles differing only by case or punctuation compare equal.
+pub fn normalize_title(title: &str) -> String {
+    title.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// Rate how likely an old title is to be a meaningful alias, from 0 to 100,
+/// along with the biggest problem found, if any.
+fn score_alias(alias: &str) -> (u8, Option<&'static str>) {
+    if alias
+        .split_once(':')
+        .is_some_and(|(namespace, _)| NON_GAME_NAMESPACES.contains(&namespace.trim().to_lowercase().as_str()))
+        || alias.to_lowercase().contains("/sandbox")
+    {
+        return (0, Some("namespace or maintenance prefix"));
+    }
+
+    if normalize_title(alias).chars().count() < 2 {
+        return (0, Some("too short"));
+    }
+
+    (100, None)
+}
+
+/// Punctuation variants are useful for matching, but an old title that's a letter or two off
+/// may just be a fixed typo. Spelling variants look the same, though, so these are only flagged for review.
+fn likely_typo(alias: &str, target: &str) -> bool {
+    let simplify = |x: &str| -> Vec<char> { normalize_title(x).chars().collect() };
+    let (alias, target) = (simplify(alias), simplify(target));
+
+    let distance = edit_distance(&alias, &target);
+    (1..=2).contains(&distance) && target.len() >= 6
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let cost = if x == y { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn do_launch_paths_match(from_steam: Option<String>, from_manifest: Option<String>, ignore_case: bool) -> bool {
     match (from_steam, from_manifest) {
         (None, None) => true,
-        (Some(from_steam), from_manifest) => normalize_launch_path(&from_steam) == from_manifest,
+        (Some(from_steam), from_manifest) => {
+            let from_steam = normalize_launch_path(&from_steam);
+            if ignore_case {
+                from_steam.map(|x| x.to_lowercase()) == from_manifest.map(|x| x.to_lowercase())
+            } else {
+                from_steam == from_manifest
+            }
+        }
         _ => false,
     }
 }
 
+/// Windows and Mac paths are usually case-insensitive, so an entry for one of those OSes should reuse
+/// an existing key for the same OS that only differs by case.
+fn find_case_insensitive_key<V>(entries: &BTreeMap<String, V>, key: String, is_same_os: impl Fn(&V) -> bool) -> String {
+    if entries.contains_key(&key) {
+        return key;
+    }
+
+    let lower = key.to_lowercase();
+    entries
+        .iter()
+        .find(|(k, v)| k.to_lowercase() == lower && is_same_os(v))
+        .map(|(k, _)| k.clone())
+        .unwrap_or(key)
+}
+
+/// What one wiki row said about a path before it was merged with other rows for the same path.
+type WikiRow<C> = (BTreeSet<Tag>, BTreeSet<C>);
+
+/// Rows for the same path share one entry, so their tags and constraints are pooled.
+/// That only keeps the meaning of each row if the rows agree on their tags or on their constraints.
+fn merged_row_problem<C: Ord>(rows: &[WikiRow<C>]) -> Option<&'static str> {
+    let tags: BTreeSet<_> = rows.iter().map(|(tags, _)| tags).collect();
+    let constraints: BTreeSet<_> = rows.iter().map(|(_, when)| when).collect();
+
+    if constraints.len() > 1 && constraints.iter().any(|x| x.is_empty()) {
+        Some("an unconstrained row is narrowed to the other rows' constraints")
+    } else if tags.len() > 1 && constraints.len() > 1 {
+        Some("each row's tags are applied under the other rows' constraints")
+    } else {
+        None
+    }
+}
+
+/// The constraints that one wiki row puts on a file path.
+fn wiki_file_constraints(path: &WikiPath) -> Vec<GameFileConstraint> {
+    let mut constraints = vec![];
+
+    let constraint = GameFileConstraint {
+        bit: path.bit,
+        os: path.os,
+        store: path.store,
+        lang: path.lang.clone(),
+        region: path.region.clone(),
+        steam_deck: path.steam_deck,
+    };
+    if !constraint.is_empty() {
+        constraints.push(constraint);
+    }
+
+    if path.ubisoft_game_launcher {
+        constraints.push(GameFileConstraint {
+            bit: path.bit,
+            os: path.os,
+            store: Some(Store::Uplay),
+            lang: path.lang.clone(),
+            region: path.region.clone(),
+            steam_deck: false,
+        });
+    }
+
+    constraints
+}
+
+/// The constraints that one wiki row puts on a registry path.
+fn wiki_registry_constraints(path: &WikiPath) -> Vec<GameRegistryConstraint> {
+    let mut constraints = vec![];
+
+    let constraint = GameRegistryConstraint {
+        bit: path.bit,
+        store: path.store,
+    };
+    if !constraint.is_empty() {
+        constraints.push(constraint);
+    }
+
+    if path.ubisoft_game_launcher {
+        constraints.push(GameRegistryConstraint {
+            bit: path.bit,
+            store: Some(Store::Uplay),
+        });
+    }
+
+    constraints
+}
+
+/// List the aliases from old titles and redirects that may just be fixed typos (see `likely_typo`).
+/// Translated titles are left out, since they often differ from the original by a letter or two.
+fn typo_aliases(manifest: &Manifest, wiki_cache: &WikiCache) -> Vec<String> {
+    manifest
+        .0
+        .iter()
+        .sorted_by(|(k1, _), (k2, _)| k1.to_lowercase().cmp(&k2.to_lowercase()))
+        .filter_map(|(alias, game)| {
+            let target = game.alias.as_ref()?;
+            let info = wiki_cache.0.get(target)?;
+            let renamed = info.renamed_from.contains(alias) || info.redirects.contains(alias);
+            (renamed && likely_typo(alias, target)).then(|| {
+                format!(
+                    "* {} -> [{}](https://www.pcgamingwiki.com/wiki/?curid={})",
+                    alias, target, info.page_id
+                )
+            })
+        })
+        .collect()
+}
+
+pub fn save_typo_aliases_list(manifest: &Manifest, wiki_cache: &WikiCache) {
+    let lines = typo_aliases(manifest, wiki_cache);
+
+    _ = std::fs::write(
+        format!("{}/data/wiki-typo-aliases.md", crate::repo()),
+        if lines.is_empty() {
+            "N/A".to_string()
+        } else {
+            lines.join("\n") + "\n"
+        },
+    );
+}
+
+/// List the paths where the manifest can't keep the meaning of each wiki row (see `merged_row_problem`),
+/// so that the articles can be split into separate rows or the constraints can be reviewed.
+pub fn save_merged_rows_list(
+    wiki_cache: &WikiCache,
+    wiki_paths: &BTreeMap<String, Vec<WikiPath>>,
+    overrides: &ManifestOverride,
+) {
+    let mut lines: Vec<String> = vec![];
+
+    for (title, info) in wiki_cache
+        .0
+        .iter()
+        .sorted_by(|(k1, _), (k2, _)| k1.to_lowercase().cmp(&k2.to_lowercase()))
+    {
+        let allow_broad = overrides.0.get(title).map(|x| &x.allow_broad);
+        let mut file_rows = BTreeMap::<&str, Vec<WikiRow<GameFileConstraint>>>::new();
+        let mut registry_rows = BTreeMap::<&str, Vec<WikiRow<GameRegistryConstraint>>>::new();
+
+        for path in wiki_paths.get(title).into_iter().flatten().filter(|x| {
+            x.usable() || (x.too_broad() && allow_broad.is_some_and(|allowed| allowed.contains(&x.composite)))
+        }) {
+            match path.kind {
+                None | Some(PathKind::File) => {
+                    let constraints = wiki_file_constraints(path);
+                    let total = constraints.len();
+                    let constraints: BTreeSet<_> = constraints
+                        .into_iter()
+                        .filter(|x| x.contradiction().is_none())
+                        .collect();
+                    if total > 0 && constraints.is_empty() {
+                        continue;
+                    }
+                    file_rows
+                        .entry(&path.composite)
+                        .or_default()
+                        .push((path.tags.clone(), constraints));
+                }
+                Some(PathKind::Registry) => {
+                    if path.os.is_some_and(|x| x != Os::Windows) {
+                        continue;
+                    }
+                    registry_rows
+                        .entry(&path.composite)
+                        .or_default()
+                        .push((path.tags.clone(), wiki_registry_constraints(path).into_iter().collect()));
+                }
+            }
+        }
+
+        let problems: Vec<_> = file_rows
+            .iter()
+            .map(|(path, rows)| (path, merged_row_problem(rows)))
+            .chain(
+                registry_rows
+                    .iter()
+                    .map(|(path, rows)| (path, merged_row_problem(rows))),
+            )
+            .filter_map(|(path, problem)| problem.map(|x| (path, x)))
+            .collect();
+        if problems.is_empty() {
+            continue;
+        }
+
+        lines.push(format!(
+            "* [{}](https://www.pcgamingwiki.com/wiki/?curid={})",
+            title, info.page_id
+        ));
+        for (path, problem) in problems {
+            lines.push(format!("  * `{}`: {}", path, problem));
+        }
+    }
+
+    _ = std::fs::write(
+        format!("{}/data/wiki-merged-rows.md", crate::repo()),
+        if lines.is_empty() {
+            "N/A".to_string()
+        } else {
+            lines.join("\n") + "\n"
+        },
+    );
+}
+
+/// Launch paths are relative to the install folder.
+/// Absolute paths (drive letters or UNC shares) can't be expressed that way, so we drop them.
 fn normalize_launch_path(raw: &str) -> Option<String> {
     if raw.contains("://") {
         return Some(raw.to_string());
     }
 
+    let raw = raw.trim();
+    let mut chars = raw.chars();
+    let has_drive = matches!(
+        (chars.next(), chars.next()),
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic()
+    );
+    let is_unc = raw.starts_with("\\\\") || raw.starts_with("//");
+    if has_drive || is_unc {
+        return None;
+    }
+
     let standardized = raw.replace('\\', "/").replace("//", "/");
     let standardized = standardized
         .trim_end_matches('/')
@@ -142,6 +435,48 @@ pub struct Manifest(pub BTreeMap<String, Game>);
 
 impl ResourceFile for Manifest {
     const FILE_NAME: &'static str = "data/manifest.yaml";
+    const YAML_STYLE: YamlStyle = YamlStyle::COMPACT;
+    const SAVE_TARGET: Option<SaveTarget> = Some(SaveTarget::Manifest);
+}
+
+/// Write coverage counts in the shields.io endpoint format,
+/// so that the README and other sites can display live badges.
+pub fn save_badges(manifest: &Manifest) {
+    let games = manifest.0.len();
+    let with_paths = manifest
+        .0
+        .values()
+        .filter(|x| !x.files.is_empty() || !x.registry.is_empty())
+        .count();
+
+    let dir = format!("{}/data/badges", crate::repo());
+    _ = std::fs::create_dir_all(&dir);
+    for (file, label, count) in [
+        ("games", "games", games),
+        ("games-with-paths", "games with paths", with_paths),
+    ] {
+        let badge = serde_json::json!({
+            "schemaVersion": 1,
+            "label": label,
+            "message": count.to_string(),
+            "color": "blue",
+        });
+        _ = std::fs::write(
+            format!("{}/{}.json", dir, file),
+            serde_json::to_string_pretty(&badge).unwrap() + "\n",
+        );
+    }
+}
+
+/// Optional additions when generating the manifest.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RefreshOptions {
+    /// Add Wine prefix equivalents of Windows paths for games on non-Steam stores.
+    pub wine: bool,
+    /// Add Proton prefix equivalents of Windows Steam Cloud paths.
+    pub proton: bool,
+    /// Add Steam DLC IDs to `steamExtra`.
+    pub dlc: bool,
 }
 
 impl Manifest {
@@ -150,47 +485,247 @@ impl Manifest {
         overrides: &ManifestOverride,
         wiki_cache: &WikiCache,
         steam_cache: &SteamCache,
+        epic_cache: &EpicCache,
+        options: RefreshOptions,
     ) -> Result<(), Error> {
         self.0.clear();
 
         let primary_ids = wiki_cache.primary_ids();
+        let demos = steam_cache.demos();
+        let mut aliases = BTreeMap::<&String, &String>::new();
 
         for (title, info) in &wiki_cache.0 {
-            if overrides.0.get(title).map(|x| x.omit).unwrap_or(false) {
+            if info.not_game || overrides.0.get(title).map(|x| x.omit).unwrap_or(false) {
                 continue;
             }
 
+            let allow_broad = overrides
+                .0
+                .get(title)
+                .map(|x| &x.allow_broad)
+                .cloned()
+                .unwrap_or_default();
+
             let mut game = Game::default();
-            game.integrate_wiki(info, title, &primary_ids);
-            for rename in &info.renamed_from {
-                if rename.to_lowercase() == title.to_lowercase() || self.0.contains_key(rename) {
-                    continue;
-                }
-                self.0.insert(
-                    rename.to_string(),
-                    Game {
-                        alias: Some(title.to_string()),
-                        ..Default::default()
-                    },
-                );
-            }
+            game.integrate_wiki(info, title, &primary_ids, &allow_broad);
             if let Some(id) = game.steam.id {
-                if let Some(info) = steam_cache.0.get(&id) {
-                    game.integrate_steam(info, overrides.0.get(title).map(|x| x.use_steam_cloud).unwrap_or(true));
+                let wiki_stub = info.stub;
+                let overridden = overrides.0.get(title);
+                let co_primary = overridden.map(|x| &x.co_primary_steam).cloned().unwrap_or_default();
+
+                // Co-primary apps are other releases of the same game, so their data is merged in.
+                for app_id in std::iter::once(id).chain(co_primary.iter().copied().filter(|x| *x != id)) {
+                    if let Some(info) = steam_cache.0.get(&app_id) {
+                        game.integrate_steam(
+                            info,
+                            overridden.map(|x| x.use_steam_cloud).unwrap_or(true),
+                            overridden.map(|x| &x.keep_launch).unwrap_or(&BTreeSet::new()),
+                            wiki_stub,
+                        );
+                        if options.proton {
+                            game.integrate_proton_paths(app_id, info);
+                        }
+                    }
+                    if options.dlc {
+                        if let Some(info) = steam_cache.0.get(&app_id) {
+                            game.id
+                                .steam_extra
+                                .extend(info.dlc.iter().filter(|x| !primary_ids.steam.contains(x)).take(MAX_DLC));
+                        }
+                    }
+                    if let Some(demo_ids) = demos.get(&app_id) {
+                        game.id
+                            .steam_extra
+                            .extend(demo_ids.iter().filter(|x| !primary_ids.steam.contains(x)));
+                    }
                 }
+
+                // The manifest only has one primary ID, so the others are still listed for detection.
+                game.id.steam_extra.extend(
+                    co_primary
+                        .iter()
+                        .filter(|x| **x != id && !primary_ids.steam.contains(x)),
+                );
             }
             if let Some(overridden) = overrides.0.get(title) {
                 game.integrate_overrides(overridden);
+                if let Some(info) = overridden.epic_app_name.as_ref().and_then(|x| epic_cache.0.get(x)) {
+                    game.integrate_epic(info);
+                }
             }
+            game.integrate_store_game_ids();
+            if options.wine {
+                game.integrate_wine_paths(overrides.0.get(title).is_some_and(|x| x.epic_app_name.is_some()));
+            }
+            game.integrate_ignore_hints();
             if !game.usable() {
                 continue;
             }
 
+            for rename in info
+                .renamed_from
+                .iter()
+                .chain(&info.redirects)
+                .chain(info.langlinks.values())
+            {
+                if rename.to_lowercase() == title.to_lowercase() {
+                    continue;
+                }
+                let (score, problem) = score_alias(rename);
+                if score < MIN_ALIAS_SCORE {
+                    println!(
+                        "[Manifest] skipping poor alias ({}, score {}): {} -> {}",
+                        problem.unwrap_or_default(),
+                        score,
+                        rename,
+                        title
+                    );
+                    continue;
+                }
+                let target = aliases.entry(rename).or_insert(title);
+                if *target != title {
+                    println!(
+                        "[Manifest] alias claimed by multiple games, keeping the first: {} -> {} | {}",
+                        rename, target, title
+                    );
+                }
+            }
+
             self.0.insert(title.to_string(), game);
         }
 
+        self.reconcile_side_ids();
+
+        // Tools often look up titles case-insensitively, so near-identical titles are ambiguous.
+        // Separate articles are both kept, but aliases may not point elsewhere from a near-identical title.
+        let mut normalized = BTreeMap::<String, String>::new();
+        for title in self.0.keys() {
+            match normalized.entry(normalize_title(title)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(title.clone());
+                }
+                Entry::Occupied(entry) => {
+                    println!(
+                        "[Manifest] titles only differ by case or punctuation, keeping both: {} | {}",
+                        entry.get(),
+                        title
+                    );
+                }
+            }
+        }
+
+        // Real games take precedence, regardless of the order that we processed them.
+        for (alias, target) in aliases {
+            if self.0.contains_key(alias) {
+                println!(
+                    "[Manifest] alias is also a game, keeping the game: {} -> {}",
+                    alias, target
+                );
+                continue;
+            }
+            let claimed = normalized
+                .entry(normalize_title(alias))
+                .or_insert_with(|| target.clone());
+            if claimed != target {
+                println!(
+                    "[Manifest] alias only differs by case or punctuation from another title, skipping: {} -> {} | {}",
+                    alias, target, claimed
+                );
+                continue;
+            }
+            self.0.insert(
+                alias.to_string(),
+                Game {
+                    alias: Some(target.to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+
+        self.prune_aliases();
+
         Ok(())
     }
+
+    /// Remove side IDs that another entry now claims as its primary ID.
+    /// The wiki's IDs are already filtered during integration,
+    /// but overrides can still assign a primary ID after that.
+    pub fn reconcile_side_ids(&mut self) {
+        let mut steam = BTreeMap::<u32, String>::new();
+        let mut gog = BTreeMap::<u64, String>::new();
+        for (title, game) in &self.0 {
+            if let Some(id) = game.steam.id {
+                steam.insert(id, title.clone());
+            }
+            if let Some(id) = game.gog.id {
+                gog.insert(id, title.clone());
+            }
+        }
+
+        for (title, game) in &mut self.0 {
+            game.id.steam_extra.retain(|id| match steam.get(id) {
+                Some(owner) if owner != title => {
+                    println!(
+                        "[Manifest] removing steamExtra ID owned by another game: {} - {} -> {}",
+                        id, title, owner
+                    );
+                    false
+                }
+                _ => true,
+            });
+            game.id.gog_extra.retain(|id| match gog.get(id) {
+                Some(owner) if owner != title => {
+                    println!(
+                        "[Manifest] removing gogExtra ID owned by another game: {} - {} -> {}",
+                        id, title, owner
+                    );
+                    false
+                }
+                _ => true,
+            });
+        }
+    }
+
+    /// Point each alias directly at its final canonical title,
+    /// and remove aliases whose target is gone or that only lead back to themselves.
+    pub fn prune_aliases(&mut self) {
+        let mut resolved = BTreeMap::<String, Option<String>>::new();
+
+        for (title, game) in &self.0 {
+            let Some(mut target) = game.alias.as_ref() else {
+                continue;
+            };
+
+            let mut seen = BTreeSet::from([title]);
+            let canonical = loop {
+                match self.0.get(target) {
+                    None => break None,
+                    Some(_) if !seen.insert(target) => break None,
+                    Some(Game { alias: Some(next), .. }) => target = next,
+                    Some(_) => break Some(target.clone()),
+                };
+            };
+
+            if canonical.as_ref() != game.alias.as_ref() {
+                resolved.insert(title.clone(), canonical);
+            }
+        }
+
+        for (title, canonical) in resolved {
+            match canonical {
+                Some(canonical) => {
+                    println!("[Manifest] collapsing alias chain: {} -> {}", title, canonical);
+                    if let Some(game) = self.0.get_mut(&title) {
+                        game.alias = Some(canonical);
+                    }
+                }
+                None => {
+                    println!("[Manifest] removing alias with stale target: {}", title);
+                    self.0.remove(&title);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -210,6 +745,8 @@ pub struct Game {
     pub install_dir: BTreeMap<String, GameInstallDirEntry>,
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub launch: BTreeMap<String, Vec<LaunchEntry>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<GameNote>,
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub registry: BTreeMap<String, GameRegistryEntry>,
     #[serde(skip_serializing_if = "SteamMetadata::is_empty")]
@@ -217,8 +754,17 @@ pub struct Game {
 }
 
 impl Game {
-    pub fn integrate_wiki(&mut self, cache: &WikiCacheEntry, title: &str, primary_ids: &PrimaryIds) {
-        self.steam = SteamMetadata { id: cache.steam };
+    pub fn integrate_wiki(
+        &mut self,
+        cache: &WikiCacheEntry,
+        title: &str,
+        primary_ids: &PrimaryIds,
+        allow_broad: &BTreeSet<String>,
+    ) {
+        self.steam = SteamMetadata {
+            id: cache.steam,
+            vr: false,
+        };
         self.gog = GogMetadata { id: cache.gog };
         self.id = IdMetadata {
             flatpak: None,
@@ -229,6 +775,7 @@ impl Game {
                 .copied()
                 .collect(),
             lutris: cache.lutris.clone(),
+            pcgw: (cache.page_id > 0).then_some(cache.page_id),
             steam_extra: cache
                 .steam_side
                 .iter()
@@ -244,59 +791,62 @@ impl Game {
             uplay: cache.cloud.uplay,
         };
 
-        let paths = cache.parse_paths(title.to_string());
+        let paths = cache.parse_paths(title.to_string(), allow_broad);
         for path in paths {
             match path.kind {
                 None | Some(PathKind::File) => {
-                    let constraints = {
-                        let mut constraints = vec![];
-
-                        let constraint = GameFileConstraint {
-                            os: path.os,
-                            store: path.store,
-                        };
-                        if !constraint.is_empty() {
-                            constraints.push(constraint);
+                    let constraints = wiki_file_constraints(&path);
+                    let key = match path.os {
+                        Some(Os::Windows) => {
+                            find_case_insensitive_key(&self.files, path.composite, GameFileEntry::is_windows_only)
                         }
-
-                        if path.ubisoft_game_launcher {
-                            constraints.push(GameFileConstraint {
-                                os: path.os,
-                                store: Some(Store::Uplay),
-                            });
+                        Some(Os::Mac) => {
+                            find_case_insensitive_key(&self.files, path.composite, GameFileEntry::is_mac_only)
                         }
-
-                        constraints
+                        _ => path.composite,
                     };
 
+                    // Contradictions usually mean that we misparsed the article.
+                    let total = constraints.len();
+                    let constraints: Vec<_> = constraints
+                        .into_iter()
+                        .filter(|x| match x.contradiction() {
+                            Some(reason) => {
+                                println!(
+                                    "[Manifest] {}: contradictory constraint for {}: {}",
+                                    title, &key, reason
+                                );
+                                false
+                            }
+                            None => true,
+                        })
+                        .collect();
+                    if total > 0 && constraints.is_empty() {
+                        continue;
+                    }
+
                     self.files
-                        .entry(path.composite)
+                        .entry(key)
                         .and_modify(|x| {
                             x.tags.extend(path.tags.clone());
                             x.when.extend(constraints.clone());
                         })
                         .or_insert_with(|| GameFileEntry {
+                            ignore: BTreeSet::new(),
                             tags: path.tags.clone().into_iter().collect(),
                             when: constraints.clone().into_iter().collect(),
                         });
                 }
                 Some(PathKind::Registry) => {
-                    let constraints = {
-                        let mut constraints = vec![];
-
-                        let constraint = GameRegistryConstraint { store: path.store };
-                        if !constraint.is_empty() {
-                            constraints.push(constraint);
-                        }
-
-                        if path.ubisoft_game_launcher {
-                            constraints.push(GameRegistryConstraint {
-                                store: Some(Store::Uplay),
-                            });
-                        }
+                    if path.os.is_some_and(|x| x != Os::Windows) {
+                        println!(
+                            "[Manifest] {}: registry path for non-Windows OS: {}",
+                            title, &path.composite
+                        );
+                        continue;
+                    }
 
-                        constraints
-                    };
+                    let constraints = wiki_registry_constraints(&path);
 
                     self.registry
                         .entry(path.composite)
@@ -316,14 +866,37 @@ impl Game {
     fn add_file_constraint(&mut self, path: String, constraint: GameFileConstraint) {
         let path = path::normalize(&path);
         if path::usable(&path) && !path.contains(':') {
-            self.files.entry(path).or_default().when.insert(constraint);
+            let path = match constraint.os {
+                Some(Os::Windows) => find_case_insensitive_key(&self.files, path, GameFileEntry::is_windows_only),
+                Some(Os::Mac) => find_case_insensitive_key(&self.files, path, GameFileEntry::is_mac_only),
+                _ => path,
+            };
+            let entry = self.files.entry(path).or_default();
+            // The wiki often documents the same Mac path without a store,
+            // so there's no need to repeat it for Steam.
+            if constraint.os == Some(Os::Mac) && entry.when.iter().any(|x| x.covers(&constraint)) {
+                return;
+            }
+            entry.when.insert(constraint);
         }
     }
 
-    pub fn integrate_steam(&mut self, cache: &SteamCacheEntry, use_steam_cloud: bool) {
+    pub fn integrate_steam(
+        &mut self,
+        cache: &SteamCacheEntry,
+        use_steam_cloud: bool,
+        keep_launch: &BTreeSet<String>,
+        wiki_stub: bool,
+    ) {
         if let Some(install_dir) = &cache.install_dir {
             self.install_dir.insert(install_dir.to_string(), GameInstallDirEntry {});
         }
+        for install_dir in &cache.old_install_dirs {
+            self.install_dir.insert(install_dir.to_string(), GameInstallDirEntry {});
+        }
+
+        // VR launch options are excluded below, but clients may still want to know about them.
+        self.steam.vr |= cache.launch.iter().any(|x| x.kind() == LaunchKind::Vr);
 
         for incoming in &cache.launch {
             if incoming.executable.is_none()
@@ -331,6 +904,8 @@ impl Game {
                 || !matches!(incoming.r#type.as_deref(), None | Some("default" | "none"))
                 || incoming.config.betakey.is_some()
                 || incoming.config.ownsdlc.is_some()
+                || (incoming.kind().is_auxiliary()
+                    && !incoming.executable.as_ref().is_some_and(|x| keep_launch.contains(x)))
             {
                 continue;
             }
@@ -352,17 +927,35 @@ impl Game {
                 bit,
                 os,
                 store: Some(Store::Steam),
+                steam_deck: incoming.config.steamdeck.as_deref() == Some("1"),
             };
 
+            // Steam uses `#`-prefixed tokens for localized descriptions,
+            // which aren't meaningful outside of Steam.
+            let description = incoming
+                .description
+                .as_ref()
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty() && !x.starts_with('#'))
+                .map(|x| x.to_string());
+
             let mut found_existing = false;
             for (existing_executable, existing_options) in self.launch.iter_mut() {
                 for existing in existing_options {
+                    let ignore_case = os == Some(Os::Windows) && existing.is_windows_only();
                     if incoming.arguments == existing.arguments
-                        && do_launch_paths_match(incoming.executable.clone(), Some(existing_executable.to_string()))
-                        && do_launch_paths_match(incoming.workingdir.clone(), existing.working_dir.clone())
+                        && do_launch_paths_match(
+                            incoming.executable.clone(),
+                            Some(existing_executable.to_string()),
+                            ignore_case,
+                        )
+                        && do_launch_paths_match(incoming.workingdir.clone(), existing.working_dir.clone(), ignore_case)
                     {
                         found_existing = true;
                         existing.when.insert(constraint.clone());
+                        if existing.description.is_none() {
+                            existing.description.clone_from(&description);
+                        }
                     }
                 }
             }
@@ -370,9 +963,15 @@ impl Game {
                 let Some(key) = incoming.executable.as_ref().and_then(|x| normalize_launch_path(x)) else {
                     continue;
                 };
+                let key = if os == Some(Os::Windows) {
+                    find_case_insensitive_key(&self.launch, key, |x| x.iter().all(LaunchEntry::is_windows_only))
+                } else {
+                    key
+                };
 
                 let candidate = LaunchEntry {
                     arguments: incoming.arguments.clone(),
+                    description,
                     when: vec![constraint.clone()].into_iter().collect(),
                     working_dir: incoming.workingdir.as_ref().and_then(|x| normalize_launch_path(x)),
                 };
@@ -383,8 +982,9 @@ impl Game {
             }
         }
 
-        // We only integrate cloud saves if there's no other save info.
-        let need_cloud = use_steam_cloud && self.files.is_empty() && self.registry.is_empty();
+        // We only integrate cloud saves if there's no other save info,
+        // or if that info comes from a stub article and may be incomplete.
+        let need_cloud = use_steam_cloud && (wiki_stub || (self.files.is_empty() && self.registry.is_empty()));
 
         for save in &cache.cloud.saves {
             if !need_cloud {
@@ -396,8 +996,12 @@ impl Game {
             };
             let os = save.platforms.first().and_then(|x| steam::parse_platform(x));
             let constraint = GameFileConstraint {
+                bit: None,
                 os,
                 store: Some(Store::Steam),
+                lang: None,
+                region: None,
+                steam_deck: false,
             };
 
             let path = save.path.trim_matches(['/', '\\']);
@@ -416,10 +1020,21 @@ impl Game {
                     continue;
                 }
 
-                let alt_os = steam::parse_os_comparison(alt.os.clone(), alt.os_compare.clone());
-                let constraint = GameFileConstraint {
-                    os: alt_os.or(os),
-                    store: Some(Store::Steam),
+                let alt_oses = steam::parse_os_comparison(alt.os.clone(), alt.os_compare.clone());
+                let constraints: Vec<_> = if alt_oses.is_empty() {
+                    vec![constraint.clone()]
+                } else {
+                    alt_oses
+                        .into_iter()
+                        .map(|os| GameFileConstraint {
+                            bit: None,
+                            os: Some(os),
+                            store: Some(Store::Steam),
+                            lang: None,
+                            region: None,
+                            steam_deck: false,
+                        })
+                        .collect()
                 };
 
                 let root = if let Some(instead) = alt.use_instead.as_ref() {
@@ -429,27 +1044,47 @@ impl Game {
                 };
                 let Some(root) = root else { continue };
 
-                let mut path = if let Some(add) = alt.add_path.as_ref() {
+                if let Some(transform) = alt.path_transforms.iter().find(|x| !x.is_supported()) {
+                    println!(
+                        "[Steam] {:?}: skipping unsupported path transform: {:?}",
+                        self.steam.id, transform
+                    );
+                    continue;
+                }
+                let mut alt_path = path.to_string();
+                for transform in &alt.path_transforms {
+                    alt_path = transform.apply(&alt_path);
+                }
+                let alt_path = alt_path
+                    .split(['/', '\\'])
+                    .filter(|x| !x.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let join = |parts: &[&str]| {
+                    parts
+                        .iter()
+                        .filter(|x| !x.is_empty())
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join("/")
+                };
+
+                let path = if let Some(add) = alt.add_path.as_ref() {
+                    let add = add.trim_matches(['/', '\\']);
                     if &save.pattern == "*" {
-                        format!("{}/{}/{}", &root, add, path)
+                        join(&[root, add, &alt_path])
                     } else if save.recursive {
-                        format!("{}/{}/{}/**/{}", &root, add, path, pattern)
+                        join(&[root, add, &alt_path, "**", pattern])
                     } else {
-                        format!("{}/{}/{}/{}", &root, add, path, pattern)
+                        join(&[root, add, &alt_path, pattern])
                     }
                 } else {
-                    format!("{}/{}/{}", &root, path, pattern)
+                    join(&[root, &alt_path, pattern])
                 };
 
-                for transform in &alt.path_transforms {
-                    if transform.find.is_empty() || transform.replace.is_empty() {
-                        // TODO: How should we handle this?
-                        continue;
-                    }
-                    path = path.replace(&transform.find, &transform.replace);
+                for constraint in constraints {
+                    self.add_file_constraint(path.clone(), constraint);
                 }
-
-                self.add_file_constraint(path, constraint.clone());
             }
         }
     }
@@ -465,30 +1100,257 @@ impl Game {
             self.id.flatpak = Some(flatpak.clone());
         }
         self.install_dir.extend(overridden.game.install_dir.clone());
+        self.notes.extend(overridden.game.notes.clone());
+        // Steam is our only automatic source of launch info,
+        // so other stores' executables have to come from here.
+        for (executable, entries) in &overridden.game.launch {
+            let existing = self.launch.entry(executable.clone()).or_default();
+            for entry in entries {
+                if !existing.contains(entry) {
+                    existing.push(entry.clone());
+                }
+            }
+        }
+        for (path, entry) in &overridden.game.files {
+            if let Some(existing) = self.files.get_mut(path) {
+                existing.ignore.extend(entry.ignore.iter().cloned());
+            }
+        }
+        if let Some(id) = overridden.ubisoft_id {
+            self.integrate_ubisoft(id);
+        }
 
         if overridden.omit_registry {
             self.registry.clear();
         }
     }
 
+    /// Steam keeps cloud saves under a folder named after the app ID,
+    /// so a wildcard in that position can be replaced by the store's game ID.
+    ///
+    /// This is only done for Steam, because the manifest doesn't record Ubisoft or Epic IDs
+    /// for tools to substitute, and the wiki's `{{p}}` template has no argument for one.
+    /// The wiki already lists Ubisoft Connect folders by their literal IDs,
+    /// and Epic's cloud save paths come from the catalog rather than an ID folder.
+    pub fn integrate_store_game_ids(&mut self) {
+        if self.steam.id.is_none() {
+            return;
+        }
+
+        let wildcard = format!("{}/userdata/{}/*", placeholder::ROOT, placeholder::STORE_USER_ID);
+        let precise = format!(
+            "{}/userdata/{}/{}",
+            placeholder::ROOT,
+            placeholder::STORE_USER_ID,
+            placeholder::STORE_GAME_ID
+        );
+
+        let matched: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(path, _)| {
+                path.strip_prefix(&wildcard)
+                    .is_some_and(|x| x.is_empty() || x.starts_with('/'))
+            })
+            .filter(|(_, entry)| {
+                entry.when.is_empty() || entry.when.iter().any(|x| matches!(x.store, None | Some(Store::Steam)))
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in matched {
+            let Some(entry) = self.files.remove(&path) else {
+                continue;
+            };
+            let path = path.replacen(&wildcard, &precise, 1);
+            match self.files.get_mut(&path) {
+                Some(existing) => {
+                    let unconstrained = existing.when.is_empty() || entry.when.is_empty();
+                    existing.ignore.extend(entry.ignore);
+                    existing.tags.extend(entry.tags);
+                    existing.when.extend(entry.when);
+                    if unconstrained {
+                        existing.when.clear();
+                    }
+                }
+                None => {
+                    self.files.insert(path, entry);
+                }
+            }
+        }
+    }
+
+    pub fn integrate_ignore_hints(&mut self) {
+        for (path, entry) in self.files.iter_mut() {
+            entry.ignore.extend(path::ignore_hints(path));
+        }
+    }
+
+    /// Epic only tells us where to look if the game uses its cloud saves.
+    pub fn integrate_epic(&mut self, cache: &EpicCacheEntry) {
+        if !self.cloud.epic {
+            return;
+        }
+
+        for (folder, os) in [
+            (&cache.cloud_save_folder, Os::Windows),
+            (&cache.cloud_save_folder_mac, Os::Mac),
+        ] {
+            let Some(path) = folder.as_ref().and_then(|x| epic::parse_path(x, os)) else {
+                continue;
+            };
+            self.add_file_constraint(
+                path,
+                GameFileConstraint {
+                    bit: None,
+                    os: Some(os),
+                    store: Some(Store::Epic),
+                    lang: None,
+                    region: None,
+                    steam_deck: false,
+                },
+            );
+        }
+    }
+
+    /// Ubisoft Connect keeps saves in its own install folder, keyed by the store's game ID,
+    /// but the wiki often only lists the paths for other stores.
+    pub fn integrate_ubisoft(&mut self, ubisoft_id: u64) {
+        let path = format!(
+            "{}/savegames/{}/{}",
+            placeholder::ROOT,
+            placeholder::STORE_USER_ID,
+            ubisoft_id
+        );
+        if self.files.contains_key(&path) {
+            return;
+        }
+
+        self.files.insert(
+            path,
+            GameFileEntry {
+                ignore: BTreeSet::new(),
+                tags: BTreeSet::from([Tag::Save]),
+                when: BTreeSet::from([GameFileConstraint {
+                    bit: None,
+                    os: Some(Os::Windows),
+                    store: Some(Store::Uplay),
+                    lang: None,
+                    region: None,
+                    steam_deck: false,
+                }]),
+            },
+        );
+    }
+
+    /// Non-Steam stores are commonly played on Linux through a Wine prefix
+    /// (e.g., via Heroic or Lutris), but the wiki rarely documents those paths,
+    /// so we derive them from the Windows paths.
+    /// The game has no Epic ID of its own, so the caller says whether there's an `epicAppName` override.
+    pub fn integrate_wine_paths(&mut self, epic_app_name: bool) {
+        if self.gog.id.is_none() && !epic_app_name && !self.cloud.epic {
+            return;
+        }
+
+        let synthesized: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(_, entry)| entry.is_windows_only())
+            .filter_map(|(path, entry)| path::to_wine_prefix(path).map(|wine| (wine, entry.tags.clone())))
+            .collect();
+
+        for (path, tags) in synthesized {
+            let entry = self.files.entry(path).or_default();
+            entry.tags.extend(tags);
+            entry.when.insert(GameFileConstraint {
+                bit: None,
+                os: None,
+                store: Some(Store::OtherWine),
+                lang: None,
+                region: None,
+                steam_deck: false,
+            });
+        }
+    }
+
+    /// Steam games without a native Linux version run in a Proton prefix,
+    /// so their Windows cloud saves end up there for Linux users.
+    pub fn integrate_proton_paths(&mut self, app_id: u32, cache: &SteamCacheEntry) {
+        if cache.supports_linux() {
+            return;
+        }
+
+        let synthesized: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(_, entry)| {
+                entry
+                    .when
+                    .iter()
+                    .any(|x| x.store == Some(Store::Steam) && x.os == Some(Os::Windows))
+            })
+            .filter_map(|(path, entry)| path::to_proton_prefix(path, app_id).map(|proton| (proton, entry.tags.clone())))
+            .collect();
+
+        for (path, tags) in synthesized {
+            let entry = self.files.entry(path).or_default();
+            entry.tags.extend(tags);
+            entry.when.insert(GameFileConstraint {
+                bit: None,
+                os: Some(Os::Linux),
+                store: Some(Store::Steam),
+                lang: None,
+                region: None,
+                steam_deck: false,
+            });
+        }
+    }
+
+    /// Every article has a PCGW ID, so that alone doesn't make an entry worth including.
     pub fn usable(&self) -> bool {
+        let ids = IdMetadata {
+            pcgw: None,
+            ..self.id.clone()
+        };
         !(self.files.is_empty()
             && self.registry.is_empty()
             && self.steam.is_empty()
             && self.gog.is_empty()
-            && self.id.is_empty())
+            && ids.is_empty())
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GameNote {
+    pub message: String,
+    /// If set, the note only applies to these file paths.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub paths: BTreeSet<String>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct GameFileEntry {
+    /// Globs relative to the path for files that aren't worth backing up.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub ignore: BTreeSet<String>,
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     pub tags: BTreeSet<Tag>,
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     pub when: BTreeSet<GameFileConstraint>,
 }
 
+impl GameFileEntry {
+    pub fn is_windows_only(&self) -> bool {
+        !self.when.is_empty() && self.when.iter().all(|x| x.os == Some(Os::Windows))
+    }
+
+    pub fn is_mac_only(&self) -> bool {
+        !self.when.is_empty() && self.when.iter().all(|x| x.os == Some(Os::Mac))
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct GameInstallDirEntry {}
@@ -507,37 +1369,85 @@ pub struct GameRegistryEntry {
 pub struct LaunchEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<String>,
+    /// Human-readable label, to help choose between multiple launch options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     pub when: BTreeSet<LaunchConstraint>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub working_dir: Option<String>,
 }
 
+impl LaunchEntry {
+    pub fn is_windows_only(&self) -> bool {
+        !self.when.is_empty() && self.when.iter().all(|x| x.os == Some(Os::Windows))
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct GameFileConstraint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub os: Option<Os>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub store: Option<Store>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub steam_deck: bool,
 }
 
 impl GameFileConstraint {
     pub fn is_empty(&self) -> bool {
-        self.os.is_none() && self.store.is_none()
+        self.bit.is_none()
+            && self.os.is_none()
+            && self.store.is_none()
+            && self.lang.is_none()
+            && self.region.is_none()
+            && !self.steam_deck
+    }
+
+    /// Whether this constraint already applies everywhere that the other one does.
+    pub fn covers(&self, other: &Self) -> bool {
+        (self.bit.is_none() || self.bit == other.bit)
+            && (self.os.is_none() || self.os == other.os)
+            && (self.store.is_none() || self.store == other.store)
+            && (self.lang.is_none() || self.lang == other.lang)
+            && (self.region.is_none() || self.region == other.region)
+            && (!self.steam_deck || other.steam_deck)
+    }
+
+    /// If the OS and store can't both apply, then this explains why.
+    pub fn contradiction(&self) -> Option<&'static str> {
+        match (self.os, self.store) {
+            (Some(Os::Dos | Os::Mac | Os::Linux), Some(Store::Microsoft)) => {
+                Some("the Microsoft Store is only for Windows")
+            }
+            // Windows builds run on the Steam Deck through Proton.
+            (Some(Os::Dos | Os::Mac), _) if self.steam_deck => {
+                Some("the Steam Deck only runs Linux builds and Windows builds through Proton")
+            }
+            _ => None,
+        }
     }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct GameRegistryConstraint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub store: Option<Store>,
 }
 
 impl GameRegistryConstraint {
     pub fn is_empty(&self) -> bool {
-        self.store.is_none()
+        self.bit.is_none() && self.store.is_none()
     }
 }
 
@@ -550,6 +1460,8 @@ pub struct LaunchConstraint {
     pub os: Option<Os>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub store: Option<Store>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub steam_deck: bool,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -557,11 +1469,14 @@ pub struct LaunchConstraint {
 pub struct SteamMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<u32>,
+    /// Steam has a VR launch option for this game.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub vr: bool,
 }
 
 impl SteamMetadata {
     pub fn is_empty(&self) -> bool {
-        self.id.is_none()
+        self.id.is_none() && !self.vr
     }
 }
 
@@ -587,13 +1502,20 @@ pub struct IdMetadata {
     pub gog_extra: BTreeSet<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lutris: Option<String>,
+    /// PCGamingWiki page ID, which stays stable across article renames.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pcgw: Option<u64>,
     #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
     pub steam_extra: BTreeSet<u32>,
 }
 
 impl IdMetadata {
     pub fn is_empty(&self) -> bool {
-        self.flatpak.is_none() && self.gog_extra.is_empty() && self.lutris.is_none() && self.steam_extra.is_empty()
+        self.flatpak.is_none()
+            && self.gog_extra.is_empty()
+            && self.lutris.is_none()
+            && self.pcgw.is_none()
+            && self.steam_extra.is_empty()
     }
 }
 
@@ -636,6 +1558,26 @@ pub struct OverrideGame {
     pub omit: bool,
     pub omit_registry: bool,
     pub use_steam_cloud: bool,
+    /// Steam launch executables to keep even if they look like a tool, server, etc.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub keep_launch: BTreeSet<String>,
+    /// Wiki paths to keep even though they're considered too broad in general.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub allow_broad: BTreeSet<String>,
+    /// Ubisoft Connect game ID, used to add the launcher's save folder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ubisoft_id: Option<u64>,
+    /// Legendary's app name for the game, used to look up Epic's cloud save info.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epic_app_name: Option<String>,
+    /// Wiki revision ID to use instead of the latest version of the article.
+    /// This is meant as a temporary measure while an article is being edit-warred or broken.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin_revision: Option<u64>,
+    /// Other Steam apps that are first-class releases of the same game (e.g., a separate VR or regional release),
+    /// whose install folders and launch options should be merged with the primary app's.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub co_primary_steam: BTreeSet<u32>,
     #[serde(flatten)]
     pub game: Game,
 }
@@ -643,3 +1585,614 @@ pub struct OverrideGame {
 impl ResourceFile for ManifestOverride {
     const FILE_NAME: &'static str = "data/manifest-override.yaml";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_constraint(os: Os, store: Option<Store>) -> GameFileConstraint {
+        GameFileConstraint {
+            bit: None,
+            os: Some(os),
+            store,
+            lang: None,
+            region: None,
+            steam_deck: false,
+        }
+    }
+
+    fn game_with_files(files: &[(&str, GameFileConstraint)]) -> Game {
+        Game {
+            files: files
+                .iter()
+                .map(|(path, constraint)| {
+                    (
+                        path.to_string(),
+                        GameFileEntry {
+                            tags: BTreeSet::from([Tag::Save]),
+                            when: BTreeSet::from([constraint.clone()]),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn proton_paths_come_from_windows_steam_paths() {
+        let files = [
+            ("<winAppData>/Game", file_constraint(Os::Windows, Some(Store::Steam))),
+            (
+                "<home>/Library/Application Support/Game",
+                file_constraint(Os::Mac, Some(Store::Steam)),
+            ),
+            ("<winDocuments>/Game", file_constraint(Os::Windows, None)),
+        ];
+        let proton = "<root>/steamapps/compatdata/1/pfx/drive_c/users/steamuser";
+
+        let mut game = game_with_files(&files);
+        game.integrate_proton_paths(1, &SteamCacheEntry::default());
+        assert_eq!(
+            vec![
+                "<home>/Library/Application Support/Game".to_string(),
+                format!("{proton}/AppData/Roaming/Game"),
+                "<winAppData>/Game".to_string(),
+                "<winDocuments>/Game".to_string(),
+            ],
+            game.files.keys().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            BTreeSet::from([file_constraint(Os::Linux, Some(Store::Steam))]),
+            game.files[&format!("{proton}/AppData/Roaming/Game")].when
+        );
+
+        // Games with a native Linux version don't use Proton.
+        let native = SteamCacheEntry {
+            launch: vec![crate::steam::Launch {
+                config: crate::steam::LaunchConfig {
+                    oslist: Some("linux".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut game = game_with_files(&files);
+        game.integrate_proton_paths(1, &native);
+        assert_eq!(game_with_files(&files), game);
+    }
+
+    #[test]
+    fn wine_paths_are_added_for_gog_and_epic_games() {
+        let files = [
+            ("<winAppData>/Game", file_constraint(Os::Windows, None)),
+            ("<home>/.local/share/Game", file_constraint(Os::Linux, None)),
+        ];
+        let wine = "<root>/<game>/drive_c/users/<osUserName>/AppData/Roaming/Game";
+        let wine_constraint = GameFileConstraint {
+            os: None,
+            ..file_constraint(Os::Windows, Some(Store::OtherWine))
+        };
+
+        let mut gog = game_with_files(&files);
+        gog.gog.id = Some(1);
+        let mut epic_cloud = game_with_files(&files);
+        epic_cloud.cloud.epic = true;
+        let mut epic_override = game_with_files(&files);
+
+        gog.integrate_wine_paths(false);
+        epic_cloud.integrate_wine_paths(false);
+        epic_override.integrate_wine_paths(true);
+        for game in [gog, epic_cloud, epic_override] {
+            assert_eq!(3, game.files.len());
+            assert_eq!(BTreeSet::from([wine_constraint.clone()]), game.files[wine].when);
+        }
+
+        let mut other = game_with_files(&files);
+        other.integrate_wine_paths(false);
+        assert_eq!(game_with_files(&files), other);
+    }
+
+    #[test]
+    fn aliases_do_not_replace_games() {
+        let wiki_cache = WikiCache(BTreeMap::from([
+            (
+                "Game (2020)".to_string(),
+                WikiCacheEntry {
+                    steam: Some(1),
+                    renamed_from: vec!["Game 2".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "Game 2".to_string(),
+                WikiCacheEntry {
+                    steam: Some(2),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Other".to_string(),
+                WikiCacheEntry {
+                    steam: Some(3),
+                    renamed_from: vec!["Other (Old)".to_string()],
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        let mut manifest = Manifest::default();
+        manifest
+            .refresh(
+                &ManifestOverride::default(),
+                &wiki_cache,
+                &SteamCache::default(),
+                &EpicCache::default(),
+                RefreshOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(None, manifest.0["Game 2"].alias);
+        assert_eq!(Some(2), manifest.0["Game 2"].steam.id);
+        assert_eq!(Some("Other".to_string()), manifest.0["Other (Old)"].alias);
+    }
+
+    #[test]
+    fn steam_deck_constraints_allow_proton() {
+        let deck = |os| GameFileConstraint {
+            os: Some(os),
+            steam_deck: true,
+            ..Default::default()
+        };
+        assert_eq!(None, deck(Os::Linux).contradiction());
+        assert_eq!(None, deck(Os::Windows).contradiction());
+        assert!(deck(Os::Mac).contradiction().is_some());
+    }
+
+    #[test]
+    fn page_ids_do_not_make_articles_usable() {
+        let wiki_cache = WikiCache(BTreeMap::from([
+            (
+                "Empty".to_string(),
+                WikiCacheEntry {
+                    page_id: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                "Game".to_string(),
+                WikiCacheEntry {
+                    page_id: 2,
+                    steam: Some(1),
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        let mut manifest = Manifest::default();
+        manifest
+            .refresh(
+                &ManifestOverride::default(),
+                &wiki_cache,
+                &SteamCache::default(),
+                &EpicCache::default(),
+                RefreshOptions::default(),
+            )
+            .unwrap();
+
+        assert!(!manifest.0.contains_key("Empty"));
+        assert_eq!(Some(2), manifest.0["Game"].id.pcgw);
+    }
+
+    #[test]
+    fn langlinks_become_aliases() {
+        let wiki_cache = WikiCache(BTreeMap::from([(
+            "The Game".to_string(),
+            WikiCacheEntry {
+                steam: Some(1),
+                langlinks: BTreeMap::from([
+                    ("de".to_string(), "Das Spiel".to_string()),
+                    ("fr".to_string(), "the game".to_string()),
+                    ("it".to_string(), "Le Game".to_string()),
+                ]),
+                renamed_from: vec!["Teh Game".to_string()],
+                ..Default::default()
+            },
+        )]));
+
+        let mut manifest = Manifest::default();
+        manifest
+            .refresh(
+                &ManifestOverride::default(),
+                &wiki_cache,
+                &SteamCache::default(),
+                &EpicCache::default(),
+                RefreshOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(Some("The Game".to_string()), manifest.0["Das Spiel"].alias);
+        assert!(!manifest.0.contains_key("the game"));
+
+        // Near-identical titles are kept, but only the renames are flagged.
+        assert_eq!(Some("The Game".to_string()), manifest.0["Le Game"].alias);
+        assert_eq!(Some("The Game".to_string()), manifest.0["Teh Game"].alias);
+        assert_eq!(
+            vec!["* Teh Game -> [The Game](https://www.pcgamingwiki.com/wiki/?curid=0)"],
+            typo_aliases(&manifest, &wiki_cache)
+        );
+    }
+
+    #[test]
+    fn aliases_do_not_collide_by_case_or_punctuation() {
+        let wiki_cache = WikiCache(BTreeMap::from([
+            (
+                "Foo".to_string(),
+                WikiCacheEntry {
+                    steam: Some(1),
+                    renamed_from: vec!["Foo: Remastered".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "FOO".to_string(),
+                WikiCacheEntry {
+                    steam: Some(2),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Bar".to_string(),
+                WikiCacheEntry {
+                    steam: Some(3),
+                    renamed_from: vec!["foo".to_string(), "Bar - Remastered".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "Foo - Remastered".to_string(),
+                WikiCacheEntry {
+                    steam: Some(4),
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        let mut manifest = Manifest::default();
+        manifest
+            .refresh(
+                &ManifestOverride::default(),
+                &wiki_cache,
+                &SteamCache::default(),
+                &EpicCache::default(),
+                RefreshOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(None, manifest.0["Foo"].alias);
+        assert_eq!(None, manifest.0["FOO"].alias);
+        assert!(!manifest.0.contains_key("foo"));
+        assert!(!manifest.0.contains_key("Foo: Remastered"));
+        assert_eq!(Some("Bar".to_string()), manifest.0["Bar - Remastered"].alias);
+    }
+
+    #[test]
+    fn co_primary_steam_ids_are_merged() {
+        let wiki_cache = WikiCache(BTreeMap::from([(
+            "Game".to_string(),
+            WikiCacheEntry {
+                steam: Some(1),
+                ..Default::default()
+            },
+        )]));
+        let steam_cache = SteamCache(BTreeMap::from([
+            (
+                1,
+                SteamCacheEntry {
+                    install_dir: Some("Game".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                2,
+                SteamCacheEntry {
+                    install_dir: Some("Game VR".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]));
+        let overrides = ManifestOverride(BTreeMap::from([(
+            "Game".to_string(),
+            OverrideGame {
+                co_primary_steam: BTreeSet::from([2]),
+                ..Default::default()
+            },
+        )]));
+
+        let mut manifest = Manifest::default();
+        manifest
+            .refresh(
+                &overrides,
+                &wiki_cache,
+                &steam_cache,
+                &EpicCache::default(),
+                RefreshOptions::default(),
+            )
+            .unwrap();
+
+        let game = &manifest.0["Game"];
+        assert_eq!(Some(1), game.steam.id);
+        assert_eq!(BTreeSet::from([2]), game.id.steam_extra);
+        assert_eq!(vec!["Game", "Game VR"], game.install_dir.keys().collect::<Vec<_>>(),);
+    }
+
+    #[test]
+    fn can_integrate_store_game_ids() {
+        let mut game = Game {
+            steam: SteamMetadata {
+                id: Some(1),
+                ..Default::default()
+            },
+            files: BTreeMap::from([
+                (
+                    "<root>/userdata/<storeUserId>/*/remote".to_string(),
+                    GameFileEntry::default(),
+                ),
+                (
+                    "<root>/userdata/<storeUserId>/1/remote".to_string(),
+                    GameFileEntry::default(),
+                ),
+                (
+                    "<root>/userdata/<storeUserId>/*.cfg".to_string(),
+                    GameFileEntry::default(),
+                ),
+                (
+                    "<root>/savegames/<storeUserId>/*".to_string(),
+                    GameFileEntry {
+                        when: BTreeSet::from([GameFileConstraint {
+                            store: Some(Store::Uplay),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        game.integrate_store_game_ids();
+
+        assert_eq!(
+            vec![
+                "<root>/savegames/<storeUserId>/*",
+                "<root>/userdata/<storeUserId>/*.cfg",
+                "<root>/userdata/<storeUserId>/1/remote",
+                "<root>/userdata/<storeUserId>/<storeGameId>/remote",
+            ],
+            game.files.keys().collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn can_detect_likely_typos() {
+        assert!(likely_typo("The Withcer", "The Witcher"));
+        assert!(likely_typo("Colour Game", "Color Game"));
+        assert!(!likely_typo("Game 2", "Game (2020)"));
+        assert!(!likely_typo("Game: Subtitle", "Game - Subtitle"));
+        assert!(!likely_typo("Gamr", "Game"));
+    }
+
+    #[test]
+    fn can_score_aliases() {
+        assert_eq!((100, None), score_alias("Game: Subtitle"));
+        assert_eq!((100, None), score_alias("Game 2"));
+        assert_eq!(0, score_alias("User:Someone/Game").0);
+        assert_eq!(0, score_alias("Game/Sandbox").0);
+        assert_eq!(0, score_alias("!").0);
+    }
+
+    #[test]
+    fn can_detect_merged_rows_that_change_meaning() {
+        let windows = GameFileConstraint {
+            os: Some(Os::Windows),
+            ..Default::default()
+        };
+        let linux = GameFileConstraint {
+            os: Some(Os::Linux),
+            ..Default::default()
+        };
+        let save = BTreeSet::from([Tag::Save]);
+        let config = BTreeSet::from([Tag::Config]);
+
+        assert_eq!(
+            None,
+            merged_row_problem(&[
+                (save.clone(), BTreeSet::from([windows.clone()])),
+                (config.clone(), BTreeSet::from([windows.clone()])),
+            ])
+        );
+        assert_eq!(
+            None,
+            merged_row_problem(&[
+                (save.clone(), BTreeSet::from([windows.clone()])),
+                (save.clone(), BTreeSet::from([linux.clone()])),
+            ])
+        );
+        assert!(merged_row_problem(&[
+            (save.clone(), BTreeSet::from([windows.clone()])),
+            (config.clone(), BTreeSet::from([linux])),
+        ])
+        .is_some());
+        assert!(merged_row_problem(&[(save.clone(), BTreeSet::new()), (save, BTreeSet::from([windows])),]).is_some());
+    }
+
+    #[test]
+    fn can_reconcile_side_ids() {
+        let mut manifest = Manifest(BTreeMap::from([
+            (
+                "Game".to_string(),
+                Game {
+                    steam: SteamMetadata { id: Some(1), vr: false },
+                    id: IdMetadata {
+                        steam_extra: BTreeSet::from([1, 2, 3]),
+                        gog_extra: BTreeSet::from([10, 20]),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ),
+            (
+                "Game: Expansion".to_string(),
+                Game {
+                    steam: SteamMetadata { id: Some(2), vr: false },
+                    gog: GogMetadata { id: Some(20) },
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        manifest.reconcile_side_ids();
+
+        assert_eq!(BTreeSet::from([1, 3]), manifest.0["Game"].id.steam_extra);
+        assert_eq!(BTreeSet::from([10]), manifest.0["Game"].id.gog_extra);
+    }
+
+    #[test]
+    fn can_prune_aliases() {
+        let alias = |target: &str| Game {
+            alias: Some(target.to_string()),
+            ..Default::default()
+        };
+        let mut manifest = Manifest(BTreeMap::from([
+            ("Game".to_string(), Game::default()),
+            ("Old".to_string(), alias("Older")),
+            ("Older".to_string(), alias("Game")),
+            ("Removed".to_string(), alias("Gone")),
+            ("Loop A".to_string(), alias("Loop B")),
+            ("Loop B".to_string(), alias("Loop A")),
+        ]));
+
+        manifest.prune_aliases();
+
+        assert_eq!(
+            Manifest(BTreeMap::from([
+                ("Game".to_string(), Game::default()),
+                ("Old".to_string(), alias("Game")),
+                ("Older".to_string(), alias("Game")),
+            ])),
+            manifest,
+        );
+    }
+
+    #[test]
+    fn windows_launch_paths_ignore_case() {
+        assert!(do_launch_paths_match(
+            Some("Bin\\Game.exe".to_string()),
+            Some("<base>/bin/game.exe".to_string()),
+            true,
+        ));
+        assert!(!do_launch_paths_match(
+            Some("Bin\\Game.exe".to_string()),
+            Some("<base>/bin/game.exe".to_string()),
+            false,
+        ));
+    }
+
+    #[test]
+    fn steam_cloud_overrides_can_exclude_an_os() {
+        let cache: SteamCacheEntry = serde_yaml::from_str(
+            r#"
+cloud:
+  saves:
+    - path: Saves
+      pattern: "*.sav"
+      platforms:
+        - all
+      root: WinMyDocuments
+  overrides:
+    - os: Windows
+      osCompare: "!="
+      root: WinMyDocuments
+      useInstead: LinuxHome
+"#,
+        )
+        .unwrap();
+
+        let mut game = Game::default();
+        game.integrate_steam(&cache, true, &BTreeSet::new(), false);
+
+        let steam = |os| GameFileConstraint {
+            bit: None,
+            os,
+            store: Some(Store::Steam),
+            lang: None,
+            region: None,
+            steam_deck: false,
+        };
+        assert_eq!(
+            BTreeSet::from([steam(None)]),
+            game.files["<winDocuments>/Saves/*.sav"].when
+        );
+        assert_eq!(
+            BTreeSet::from([steam(Some(Os::Mac)), steam(Some(Os::Linux))]),
+            game.files["<home>/Saves/*.sav"].when
+        );
+    }
+
+    #[test]
+    fn steam_cloud_transforms_can_replace_whole_path() {
+        // Based on My Time at Sandrock (1084600).
+        let cache: SteamCacheEntry = serde_yaml::from_str(
+            r#"
+cloud:
+  saves:
+    - path: Pathea Games/My Time at Sandrock
+      pattern: "*.save"
+      root: WinAppDataLocalLow
+  overrides:
+    - os: Linux
+      osCompare: "="
+      pathTransforms:
+        - find: ""
+          replace: ".config/unity3d/Pathea Games/My Time at Sandrock"
+      root: WinAppDataLocalLow
+      useInstead: LinuxHome
+"#,
+        )
+        .unwrap();
+
+        let mut game = Game::default();
+        game.integrate_steam(&cache, true, &BTreeSet::new(), false);
+
+        assert!(game
+            .files
+            .contains_key("<home>/.config/unity3d/Pathea Games/My Time at Sandrock/*.save"));
+    }
+
+    #[test]
+    fn can_normalize_launch_path() {
+        assert_eq!(Some("<base>/game.exe".to_string()), normalize_launch_path("game.exe"));
+        assert_eq!(
+            Some("<base>/bin/game.exe".to_string()),
+            normalize_launch_path("\\bin\\game.exe")
+        );
+        assert_eq!(
+            Some("<base>/bin/game.exe".to_string()),
+            normalize_launch_path(".\\bin\\\\game.exe")
+        );
+        assert_eq!(Some("<base>/bin".to_string()), normalize_launch_path("bin/"));
+        assert_eq!(None, normalize_launch_path("."));
+        assert_eq!(None, normalize_launch_path(".\\"));
+        assert_eq!(None, normalize_launch_path(""));
+        assert_eq!(None, normalize_launch_path("C:\\Games\\game.exe"));
+        assert_eq!(None, normalize_launch_path("c:/Games/game.exe"));
+        assert_eq!(None, normalize_launch_path("\\\\server\\share\\game.exe"));
+        assert_eq!(None, normalize_launch_path("//server/share/game.exe"));
+        assert_eq!(
+            Some("steam://run/123".to_string()),
+            normalize_launch_path("steam://run/123")
+        );
+    }
+}
@@ -2,15 +2,19 @@ use itertools::Itertools;
 
 use crate::{
     manifest::{Manifest, ManifestOverride},
-    wiki::WikiCache,
-    REPO,
+    repo,
+    wiki::{WikiCache, WikiCacheEntry},
 };
 
-pub fn save_missing_games(wiki_cache: &WikiCache, manifest: &Manifest, overrides: &ManifestOverride) {
-    let lines: Vec<String> = wiki_cache
+/// Wiki games that ended up without any files or registry in the manifest.
+pub fn find_missing_games<'a>(
+    wiki_cache: &'a WikiCache,
+    manifest: &'a Manifest,
+    overrides: &'a ManifestOverride,
+) -> impl Iterator<Item = (&'a String, &'a WikiCacheEntry)> {
+    wiki_cache
         .0
         .iter()
-        .sorted_by(|(k1, _), (k2, _)| k1.to_lowercase().cmp(&k2.to_lowercase()))
         .filter(|(k, _)| {
             manifest
                 .0
@@ -18,12 +22,17 @@ pub fn save_missing_games(wiki_cache: &WikiCache, manifest: &Manifest, overrides
                 .map(|x| x.files.is_empty() && x.registry.is_empty())
                 .unwrap_or(true)
         })
-        .filter(|(k, _)| overrides.0.get(*k).map(|x| !x.omit).unwrap_or(true))
+        .filter(|(k, v)| !v.not_game && overrides.0.get(*k).map(|x| !x.omit).unwrap_or(true))
+}
+
+pub fn save_missing_games(wiki_cache: &WikiCache, manifest: &Manifest, overrides: &ManifestOverride) {
+    let lines: Vec<String> = find_missing_games(wiki_cache, manifest, overrides)
+        .sorted_by(|(k1, _), (k2, _)| k1.to_lowercase().cmp(&k2.to_lowercase()))
         .map(|(k, v)| format!("* [{}](https://www.pcgamingwiki.com/wiki/?curid={})", k, v.page_id))
         .collect();
 
     _ = std::fs::write(
-        format!("{}/data/missing.md", REPO),
+        format!("{}/data/missing.md", repo()),
         if lines.is_empty() {
             "N/A".to_string()
         } else {
@@ -31,3 +40,46 @@ pub fn save_missing_games(wiki_cache: &WikiCache, manifest: &Manifest, overrides
         },
     );
 }
+
+/// Append any games that disappeared during this run to a log,
+/// along with their last known data, so that accidental removals can be recovered.
+pub fn save_removed_games(old: &Manifest, manifest: &Manifest, wiki_cache: &WikiCache) {
+    let mut lines: Vec<String> = vec![];
+
+    for (title, game) in old
+        .0
+        .iter()
+        .sorted_by(|(k1, _), (k2, _)| k1.to_lowercase().cmp(&k2.to_lowercase()))
+    {
+        if manifest.0.contains_key(title) {
+            continue;
+        }
+
+        let reason = match wiki_cache.0.get(title) {
+            Some(info) if info.not_game => "wiki article is no longer a game".to_string(),
+            Some(_) => "no usable data".to_string(),
+            None => match wiki_cache.0.iter().find(|(_, v)| v.renamed_from.contains(title)) {
+                Some((new_title, _)) => format!("wiki article renamed to '{}'", new_title),
+                None => "wiki article deleted or no longer in a relevant category".to_string(),
+            },
+        };
+
+        lines.push(format!("### {}", title));
+        lines.push(format!("Reason: {}", reason));
+        lines.push("".to_string());
+        lines.push("```yaml".to_string());
+        lines.push(serde_yaml::to_string(game).unwrap_or_default().trim_end().to_string());
+        lines.push("```".to_string());
+        lines.push("".to_string());
+    }
+
+    if lines.is_empty() {
+        return;
+    }
+
+    let path = format!("{}/data/removed.md", repo());
+    let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+    content.push_str(&format!("\n## {}\n\n", chrono::Utc::now().format("%Y-%m-%d")));
+    content.push_str(&(lines.join("\n")));
+    _ = std::fs::write(path, content);
+}
@@ -0,0 +1,197 @@
+use std::{process::Command, sync::Mutex};
+
+use crate::{manifest::Manifest, repo, resource::ResourceFile, Error};
+
+/// Alert if more than this many games disappear in one run.
+const MAX_REMOVALS: usize = 25;
+/// Alert if the manifest shrinks by more than this percentage in one run.
+const MAX_SHRINKAGE_PERCENT: usize = 2;
+/// Only include this many characters of each error in the failure report.
+const MAX_ERROR_EXCERPT: usize = 500;
+
+static ANOMALIES: Mutex<Vec<String>> = Mutex::new(vec![]);
+static FAILURES: Mutex<Vec<Failure>> = Mutex::new(vec![]);
+
+/// Something that a bulk run couldn't process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailedItem {
+    WikiPage { title: String, page_id: u64 },
+    SteamApp(u32),
+    GogProduct(u64),
+}
+
+impl FailedItem {
+    /// Find the item named by the error's context, if any.
+    fn of(error: &Error) -> Option<Self> {
+        match error {
+            Error::WikiPage { title, page_id, .. } => Some(Self::WikiPage {
+                title: title.clone(),
+                page_id: *page_id,
+            }),
+            Error::SteamApp { app_id, .. } => Some(Self::SteamApp(*app_id)),
+            Error::GogProduct { product_id, .. } => Some(Self::GogProduct(*product_id)),
+            _ => None,
+        }
+    }
+
+    fn link(&self) -> String {
+        match self {
+            Self::WikiPage { title, page_id } if *page_id > 0 => {
+                format!("[{}](https://www.pcgamingwiki.com/wiki/?curid={})", title, page_id)
+            }
+            Self::WikiPage { title, .. } => title.clone(),
+            Self::SteamApp(app_id) => format!("[Steam app {}](https://steamdb.info/app/{})", app_id, app_id),
+            Self::GogProduct(product_id) => format!(
+                "[GOG product {}](https://www.gogdb.org/product/{})",
+                product_id, product_id
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// None if the failure wasn't tied to a specific item.
+    pub item: Option<FailedItem>,
+    pub error: String,
+    pub cause: &'static str,
+}
+
+impl Failure {
+    pub fn new(error: &Error) -> Self {
+        Self {
+            item: FailedItem::of(error),
+            error: error.root().to_string().chars().take(MAX_ERROR_EXCERPT).collect(),
+            cause: error.suspected_cause(),
+        }
+    }
+}
+
+/// Record something unusual that a maintainer should look at.
+pub fn anomaly(message: String) {
+    eprintln!("Anomaly: {}", message);
+    ANOMALIES.lock().unwrap().push(message);
+}
+
+pub fn check_manifest_changes(old: &Manifest, manifest: &Manifest) {
+    let removed = old.0.keys().filter(|k| !manifest.0.contains_key(*k)).count();
+    if removed > MAX_REMOVALS {
+        anomaly(format!("{} games were removed", removed));
+    }
+
+    let (before, after) = (old.0.len(), manifest.0.len());
+    if after < before && (before - after) * 100 > before * MAX_SHRINKAGE_PERCENT {
+        anomaly(format!("Manifest shrank from {} to {} games", before, after));
+    }
+}
+
+/// Check whether the manifest grew more than expected since the last run,
+/// such as from a parser bug that produces lots of new paths.
+/// Returns false if any limit was exceeded.
+pub fn check_manifest_growth(
+    old: &Manifest,
+    manifest: &Manifest,
+    max_game_growth: usize,
+    max_size_growth: usize,
+) -> bool {
+    if old == manifest {
+        return true;
+    }
+
+    let mut ok = true;
+
+    let (before, after) = (old.0.len(), manifest.0.len());
+    if before > 0 && after > before && (after - before) * 100 > before * max_game_growth {
+        anomaly(format!("Manifest grew from {} to {} games", before, after));
+        ok = false;
+    }
+
+    let before = Manifest::load_raw(&Manifest::path())
+        .map(|x| x.len())
+        .unwrap_or_default();
+    let after = ResourceFile::serialize(manifest).len();
+    if before > 0 && after > before && (after - before) * 100 > before * max_size_growth {
+        anomaly(format!("Manifest file grew from {} to {} bytes", before, after));
+        ok = false;
+    }
+
+    ok
+}
+
+/// Record an item that failed during a bulk run, for the failure report.
+/// The error should name the item (e.g., via `Error::for_wiki_page`).
+pub fn failure(error: &Error) {
+    FAILURES.lock().unwrap().push(Failure::new(error));
+}
+
+/// Write a Markdown report of this run's failures to `data/last-failure.md`,
+/// ready to paste into a tracking issue.
+/// If nothing failed, then any previous report is removed.
+pub fn save_failure_report(run_failure: Option<Failure>) {
+    let path = format!("{}/data/last-failure.md", repo());
+
+    let mut failures = FAILURES.lock().unwrap().clone();
+    // If the run stopped because of an item, then that item already explains it.
+    if let Some(run_failure) = run_failure {
+        if !failures
+            .iter()
+            .any(|x| x.error == run_failure.error && x.item == run_failure.item)
+        {
+            failures.insert(0, run_failure);
+        }
+    }
+    if failures.is_empty() {
+        _ = std::fs::remove_file(path);
+        return;
+    }
+
+    let mut lines = vec![
+        "## Automatic import fails".to_string(),
+        "".to_string(),
+        format!(
+            "The bulk import on {} had {} failure(s).",
+            chrono::Utc::now().format("%Y-%m-%d"),
+            failures.len()
+        ),
+    ];
+
+    for failure in &failures {
+        lines.push("".to_string());
+        match &failure.item {
+            Some(item) => lines.push(format!("### {}", item.link())),
+            None => lines.push("### Run aborted".to_string()),
+        }
+        lines.push("".to_string());
+        lines.push("```".to_string());
+        lines.push(failure.error.trim().to_string());
+        lines.push("```".to_string());
+        lines.push("".to_string());
+        lines.push(format!("Suspected cause: {}.", failure.cause));
+    }
+
+    _ = std::fs::write(path, lines.join("\n") + "\n");
+}
+
+/// Send any recorded anomalies to a webhook as a JSON POST.
+pub fn send(webhook: &str) {
+    let anomalies = ANOMALIES.lock().unwrap().clone();
+    if anomalies.is_empty() {
+        return;
+    }
+
+    let payload = serde_json::json!({
+        "text": format!("ludusavi-manifest: {} anomalies detected\n{}", anomalies.len(), anomalies.join("\n")),
+        "anomalies": anomalies,
+    });
+
+    let output = Command::new("python")
+        .arg(format!("{}/scripts/send-webhook.py", repo()))
+        .arg(webhook)
+        .arg(payload.to_string())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => eprintln!("Webhook failure: {}", String::from_utf8_lossy(&output.stderr)),
+        Err(e) => eprintln!("Webhook failure: {e}"),
+    }
+}
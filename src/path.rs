@@ -1,11 +1,132 @@
+use std::collections::BTreeSet;
+
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::manifest::placeholder;
+use crate::{manifest::placeholder, resource::ResourceFile};
+
+/// Paths that are present whether or not a game is installed.
+pub static BROAD_PATHS: Lazy<BroadPaths> = Lazy::new(|| BroadPaths::load().unwrap());
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BroadPaths {
+    pub file: Vec<String>,
+    pub registry: Vec<String>,
+}
+
+impl ResourceFile for BroadPaths {
+    const FILE_NAME: &'static str = "data/broad-paths.yaml";
+}
+
+/// Well-known files and folders inside of save folders that aren't worth backing up.
+pub static IGNORE_PATHS: Lazy<IgnorePaths> = Lazy::new(|| IgnorePaths::load().unwrap());
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct IgnorePaths(pub Vec<IgnoreRule>);
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct IgnoreRule {
+    pub pattern: String,
+    pub ignore: BTreeSet<String>,
+}
+
+impl ResourceFile for IgnorePaths {
+    const FILE_NAME: &'static str = "data/ignore-paths.yaml";
+}
+
+impl IgnoreRule {
+    pub fn regex(&self) -> Result<Regex, regex::Error> {
+        Regex::new(&format!("(?i){}", self.pattern))
+    }
+}
+
+/// Find well-known files and folders that aren't worth backing up inside of this path.
+pub fn ignore_hints(path: &str) -> BTreeSet<String> {
+    static RULES: Lazy<Vec<(Regex, &BTreeSet<String>)>> = Lazy::new(|| {
+        IGNORE_PATHS
+            .0
+            .iter()
+            .filter_map(|rule| rule.regex().ok().map(|regex| (regex, &rule.ignore)))
+            .collect()
+    });
+
+    RULES
+        .iter()
+        .filter(|(regex, _)| regex.is_match(path))
+        .flat_map(|(_, ignore)| ignore.iter().cloned())
+        .collect()
+}
+
+/// A SteamID64 for an individual account, as a whole path segment.
+static STEAM_ID_64: Lazy<Regex> = Lazy::new(|| Regex::new(r"^7656119\d{10}$").unwrap());
+/// Folders directly under Steam's `userdata` are always named after a Steam3 account ID.
+static STEAM_USERDATA_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(<root>/userdata/)\d+(/|$)").unwrap());
+
+/// Whether the path contains someone's literal user ID,
+/// which `normalize` will replace with `<storeUserId>`.
+pub fn has_literal_user_id(path: &str) -> bool {
+    let path = path.trim().replace('\\', "/");
+    path.split('/').any(|x| STEAM_ID_64.is_match(x)) || STEAM_USERDATA_ID.is_match(&path)
+}
+
+/// Replace a specific user's home folder, as in a path copied from someone's own system,
+/// with the equivalent placeholder.
+/// Returns `None` if the path doesn't name a specific user.
+fn replace_user_name(path: &str) -> Option<String> {
+    /// Folders under `Users` that don't belong to a person.
+    const SHARED: &[&str] = &["all users", "default", "default user", "public", "shared", "steamuser"];
+
+    static USER_FOLDERS: Lazy<Vec<(Regex, &str)>> = Lazy::new(|| {
+        vec![
+            (
+                Regex::new(r"(?i)^()[a-z]:/Users/([^/<>*]+)(/|$)").unwrap(),
+                "%userprofile%",
+            ),
+            (Regex::new(r"^()/Users/([^/<>*]+)(/|$)").unwrap(), placeholder::HOME),
+            (Regex::new(r"^()/home/([^/<>*]+)(/|$)").unwrap(), placeholder::HOME),
+            (
+                Regex::new(r"(?i)(/drive_c/users/)([^/<>*]+)(/|$)").unwrap(),
+                placeholder::OS_USER_NAME,
+            ),
+        ]
+    });
+
+    for (pattern, replacement) in USER_FOLDERS.iter() {
+        let Some(captures) = pattern.captures(path) else {
+            continue;
+        };
+        if SHARED.contains(&captures[2].to_lowercase().as_str()) {
+            continue;
+        }
+        let matched = captures.get(0).unwrap();
+        return Some(format!(
+            "{}{}{}{}{}",
+            &path[..matched.start()],
+            &captures[1],
+            replacement,
+            &captures[3],
+            &path[matched.end()..]
+        ));
+    }
+
+    None
+}
+
+/// Whether the path contains a specific user's home folder,
+/// which `normalize` will replace with a placeholder.
+pub fn has_hardcoded_user_name(path: &str) -> bool {
+    replace_user_name(&path.trim().replace('\\', "/")).is_some()
+}
 
 pub fn normalize(path: &str) -> String {
     let mut path = path.trim().trim_end_matches(['/', '\\']).replace('\\', "/");
 
+    if let Some(replaced) = replace_user_name(&path) {
+        path = replaced;
+    }
+
     if path == "~" || path.starts_with("~/") {
         path = path.replacen('~', placeholder::HOME, 1);
     }
@@ -23,6 +144,11 @@ pub fn normalize(path: &str) -> String {
     static APP_DATA_LOCAL_2: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)%userprofile%/AppData/Local/").unwrap());
     static USER_PROFILE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)%userprofile%").unwrap());
     static DOCUMENTS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)%userprofile%/Documents").unwrap());
+    static MAC_LIBRARY: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^<home>/Library(/|$)").unwrap());
+    static MAC_APP_SUPPORT: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)^<home>/Library/Application Support(/|$)").unwrap());
+    static MAC_PREFERENCES: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^<home>/Library/Preferences(/|$)").unwrap());
+    static MAC_CONTAINERS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^<home>/Library/Containers(/|$)").unwrap());
 
     for (pattern, replacement) in [
         (&CONSECUTIVE_SLASHES, "/"),
@@ -38,10 +164,30 @@ pub fn normalize(path: &str) -> String {
         (&APP_DATA_LOCAL_2, &format!("{}/", placeholder::WIN_LOCAL_APP_DATA)),
         (&USER_PROFILE, placeholder::HOME),
         (&DOCUMENTS, placeholder::WIN_DOCUMENTS),
+        (&MAC_LIBRARY, "<home>/Library${1}"),
+        (&MAC_APP_SUPPORT, "<home>/Library/Application Support${1}"),
+        (&MAC_PREFERENCES, "<home>/Library/Preferences${1}"),
+        (&MAC_CONTAINERS, "<home>/Library/Containers${1}"),
+        (&STEAM_USERDATA_ID, "${1}<storeUserId>${2}"),
     ] {
         path = pattern.replace_all(&path, replacement).to_string();
     }
 
+    // This is checked per segment, since adjacent IDs would share the separator in a regex match.
+    if path.split('/').any(|x| STEAM_ID_64.is_match(x)) {
+        path = path
+            .split('/')
+            .map(|x| {
+                if STEAM_ID_64.is_match(x) {
+                    placeholder::STORE_USER_ID
+                } else {
+                    x
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+    }
+
     for (pattern, replacement) in [
         ("{64BitSteamID}", placeholder::STORE_USER_ID),
         ("{Steam3AccountID}", placeholder::STORE_USER_ID),
@@ -52,8 +198,62 @@ pub fn normalize(path: &str) -> String {
     path
 }
 
-fn too_broad(path: &str) -> bool {
-    use placeholder::{BASE, HOME, ROOT, STORE_USER_ID, WIN_APP_DATA, WIN_DIR, WIN_DOCUMENTS, XDG_CONFIG, XDG_DATA};
+/// Map a Windows path into the equivalent location inside of a generic Wine prefix.
+/// Paths that don't depend on the prefix (like `<base>`) are not mapped.
+pub fn to_wine_prefix(path: &str) -> Option<String> {
+    use placeholder::{GAME, OS_USER_NAME, ROOT};
+
+    to_prefix(path, &format!("{ROOT}/{GAME}/drive_c"), OS_USER_NAME)
+}
+
+/// Proton keeps a separate prefix for each app under the Steam library,
+/// always with the same user name.
+pub fn to_proton_prefix(path: &str, app_id: u32) -> Option<String> {
+    use placeholder::ROOT;
+
+    to_prefix(
+        path,
+        &format!("{ROOT}/steamapps/compatdata/{app_id}/pfx/drive_c"),
+        "steamuser",
+    )
+}
+
+fn to_prefix(path: &str, prefix: &str, user_name: &str) -> Option<String> {
+    use placeholder::{HOME, WIN_APP_DATA, WIN_DIR, WIN_DOCUMENTS, WIN_LOCAL_APP_DATA, WIN_PROGRAM_DATA, WIN_PUBLIC};
+
+    let user = format!("{prefix}/users/{user_name}");
+
+    for (from, to) in [
+        (WIN_APP_DATA, format!("{user}/AppData/Roaming")),
+        (WIN_LOCAL_APP_DATA, format!("{user}/AppData/Local")),
+        (WIN_DOCUMENTS, format!("{user}/Documents")),
+        (HOME, user.clone()),
+        (WIN_PUBLIC, format!("{prefix}/users/Public")),
+        (WIN_PROGRAM_DATA, format!("{prefix}/ProgramData")),
+        (WIN_DIR, format!("{prefix}/windows")),
+    ] {
+        if path == from {
+            return Some(to);
+        } else if let Some(rest) = path.strip_prefix(&format!("{from}/")) {
+            return Some(format!("{to}/{rest}"));
+        }
+    }
+
+    None
+}
+
+/// Checks for a pattern like `<placeholder>/*/...`,
+/// which would match every folder under a location shared by many programs.
+pub fn wildcard_under_avoided_root(path: &str) -> bool {
+    use placeholder::STORE_USER_ID;
+
+    placeholder::AVOID_WILDCARDS
+        .iter()
+        .any(|item| path.starts_with(&format!("{item}/*")) || path.starts_with(&format!("{item}/{STORE_USER_ID}")))
+}
+
+pub fn too_broad(path: &str) -> bool {
+    use placeholder::STORE_USER_ID;
 
     let path_lower = path.to_lowercase();
 
@@ -63,45 +263,11 @@ fn too_broad(path: &str) -> bool {
         }
     }
 
-    for item in placeholder::AVOID_WILDCARDS {
-        if path.starts_with(&format!("{item}/*")) || path.starts_with(&format!("{item}/{STORE_USER_ID}")) {
-            return true;
-        }
+    if wildcard_under_avoided_root(path) {
+        return true;
     }
 
-    // These paths are present whether or not the game is installed.
-    // If possible, they should be narrowed down on the wiki.
-    for item in [
-        format!("{BASE}/{STORE_USER_ID}"), // because `<storeUserId>` is handled as `*`
-        format!("{HOME}/Documents"),
-        format!("{HOME}/Saved Games"),
-        format!("{HOME}/AppData"),
-        format!("{HOME}/AppData/Local"),
-        format!("{HOME}/AppData/Local/Packages"),
-        format!("{HOME}/AppData/LocalLow"),
-        format!("{HOME}/AppData/Roaming"),
-        format!("{HOME}/Documents/My Games"),
-        format!("{HOME}/Library/Application Support"),
-        format!("{HOME}/Library/Application Support/UserData"),
-        format!("{HOME}/Library/Preferences"),
-        format!("{HOME}/.renpy"),
-        format!("{HOME}/.renpy/persistent"),
-        format!("{HOME}/Library"),
-        format!("{HOME}/Library/RenPy"),
-        format!("{HOME}/Telltale Games"),
-        format!("{ROOT}/config"),
-        format!("{WIN_APP_DATA}/MMFApplications"),
-        format!("{WIN_APP_DATA}/RenPy"),
-        format!("{WIN_APP_DATA}/RenPy/persistent"),
-        format!("{WIN_DIR}/win.ini"),
-        format!("{WIN_DIR}/SysWOW64"),
-        format!("{WIN_DOCUMENTS}/My Games"),
-        format!("{WIN_DOCUMENTS}/Telltale Games"),
-        format!("{XDG_CONFIG}/unity3d"),
-        format!("{XDG_DATA}/unity3d"),
-        "C:/Program Files".to_string(),
-        "C:/Program Files (x86)".to_string(),
-    ] {
+    for item in &BROAD_PATHS.file {
         let item = item.to_lowercase();
         if path_lower == item
             || path_lower.starts_with(&format!("{item}/*"))
@@ -146,3 +312,57 @@ pub fn usable(path: &str) -> bool {
         && !too_broad(path)
         && !UNPRINTABLE.is_match(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_paths_map_into_wine_prefix() {
+        let user = "<root>/<game>/drive_c/users/<osUserName>";
+        assert_eq!(
+            Some(format!("{user}/AppData/Local/Game")),
+            to_wine_prefix("<winLocalAppData>/Game")
+        );
+        assert_eq!(
+            Some(format!("{user}/Saved Games")),
+            to_wine_prefix("<home>/Saved Games")
+        );
+        assert_eq!(
+            Some("<root>/<game>/drive_c/users/Public/Game".to_string()),
+            to_wine_prefix("<winPublic>/Game")
+        );
+        assert_eq!(None, to_wine_prefix("<base>/saves"));
+        assert_eq!(None, to_wine_prefix("<winAppDataGame>"));
+    }
+
+    #[test]
+    fn literal_user_ids_become_placeholders() {
+        let cases = [
+            (
+                "<winDocuments>/Game/76561197960287930/save.dat",
+                "<winDocuments>/Game/<storeUserId>/save.dat",
+            ),
+            ("76561197960287930/save.dat", "<storeUserId>/save.dat"),
+            ("<base>/saves/76561197960287930", "<base>/saves/<storeUserId>"),
+            (
+                "<base>/76561197960287930/76561197960287931/save.dat",
+                "<base>/<storeUserId>/<storeUserId>/save.dat",
+            ),
+            (
+                "<root>/userdata/22202/12345/remote",
+                "<root>/userdata/<storeUserId>/12345/remote",
+            ),
+            // Other 17-digit numbers aren't SteamID64s.
+            ("<base>/saves/12345678901234567", "<base>/saves/12345678901234567"),
+            ("<base>/saves/765611979602879301", "<base>/saves/765611979602879301"),
+            ("<base>/saves/x76561197960287930", "<base>/saves/x76561197960287930"),
+            ("<base>/userdata/22202", "<base>/userdata/22202"),
+        ];
+
+        for (raw, expected) in cases {
+            assert_eq!(expected, normalize(raw), "{}", raw);
+            assert_eq!(raw != expected, has_literal_user_id(raw), "{}", raw);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    gog::GogCache,
+    manifest::{Manifest, ManifestOverride},
+    missing, repo,
+    resource::ResourceFile,
+    steam::{self, SteamCache},
+    wiki::{PathSummary, WikiCache, WikiPath},
+};
+
+/// Aggregate counts about the health of the data,
+/// so that a static dashboard can track them over time from the file's history.
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityReport {
+    pub games: usize,
+    pub games_with_paths: usize,
+    pub aliases: usize,
+    pub wiki: WikiQuality,
+    pub steam: SteamQuality,
+    pub overrides: OverrideQuality,
+    pub audit: AuditQuality,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WikiQuality {
+    pub pages: usize,
+    pub irregular_pages: usize,
+    pub irregular_paths: usize,
+    pub semiregular_pages: usize,
+    pub semiregular_paths: usize,
+    pub malformed_pages: usize,
+    pub missing_games: usize,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteamQuality {
+    pub irregular_apps: usize,
+    /// Games whose only paths come from Steam Auto-Cloud.
+    pub cloud_only_games: usize,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideQuality {
+    pub total: usize,
+    pub omit: usize,
+    pub omit_registry: usize,
+    pub use_steam_cloud: usize,
+    pub allow_broad: usize,
+    pub pin_revision: usize,
+    pub co_primary_steam: usize,
+    pub files_or_registry: usize,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQuality {
+    /// GOG IDs that GOG's API does not know about (as of the last `gog-ids` run).
+    pub gog_missing: usize,
+    /// GOG IDs that point to a pack instead of a game (as of the last `gog-ids` run).
+    pub gog_packs: usize,
+    pub steam_unmapped_roots: usize,
+    pub steam_unmapped_root_apps: usize,
+}
+
+impl QualityReport {
+    pub fn new(
+        manifest: &Manifest,
+        overrides: &ManifestOverride,
+        wiki_cache: &WikiCache,
+        wiki_paths: &BTreeMap<String, Vec<WikiPath>>,
+        steam_cache: &SteamCache,
+        gog_cache: &GogCache,
+    ) -> Self {
+        let mut out = Self::default();
+
+        for game in manifest.0.values() {
+            if game.alias.is_some() {
+                out.aliases += 1;
+                continue;
+            }
+            out.games += 1;
+            if !game.files.is_empty() || !game.registry.is_empty() {
+                out.games_with_paths += 1;
+            }
+        }
+
+        out.wiki.pages = wiki_cache.0.len();
+        for (title, info) in &wiki_cache.0 {
+            if info.malformed {
+                out.wiki.malformed_pages += 1;
+            }
+
+            let allow_broad = overrides
+                .0
+                .get(title)
+                .map(|x| x.allow_broad.clone())
+                .unwrap_or_default();
+            let paths = PathSummary::new(
+                wiki_paths.get(title).map(|x| x.as_slice()).unwrap_or_default(),
+                &allow_broad,
+            );
+            if paths.irregular > 0 {
+                out.wiki.irregular_pages += 1;
+                out.wiki.irregular_paths += paths.irregular;
+            }
+            if paths.semiregular > 0 {
+                out.wiki.semiregular_pages += 1;
+                out.wiki.semiregular_paths += paths.semiregular;
+            }
+
+            let Some(game) = manifest.0.get(title) else {
+                continue;
+            };
+            let cloud = game
+                .steam
+                .id
+                .and_then(|id| steam_cache.0.get(&id))
+                .is_some_and(|x| !x.cloud.saves.is_empty());
+            if cloud && paths.usable == 0 && !game.files.is_empty() && game.registry.is_empty() {
+                out.steam.cloud_only_games += 1;
+            }
+        }
+        out.wiki.missing_games = missing::find_missing_games(wiki_cache, manifest, overrides).count();
+
+        out.steam.irregular_apps = steam_cache.0.values().filter(|x| x.irregular).count();
+
+        for info in overrides.0.values() {
+            out.overrides.total += 1;
+            out.overrides.omit += info.omit as usize;
+            out.overrides.omit_registry += info.omit_registry as usize;
+            out.overrides.use_steam_cloud += info.use_steam_cloud as usize;
+            out.overrides.allow_broad += !info.allow_broad.is_empty() as usize;
+            out.overrides.pin_revision += info.pin_revision.is_some() as usize;
+            out.overrides.co_primary_steam += !info.co_primary_steam.is_empty() as usize;
+            out.overrides.files_or_registry += (!info.game.files.is_empty() || !info.game.registry.is_empty()) as usize;
+        }
+
+        let gog_ids: BTreeSet<_> = manifest
+            .0
+            .values()
+            .flat_map(|x| x.gog.id.iter().chain(&x.id.gog_extra))
+            .collect();
+        for id in gog_ids {
+            let Some(info) = gog_cache.0.get(id) else {
+                continue;
+            };
+            if info.missing {
+                out.audit.gog_missing += 1;
+            } else if info.is_pack() {
+                out.audit.gog_packs += 1;
+            }
+        }
+
+        let unmapped = steam::find_unmapped_roots(steam_cache);
+        out.audit.steam_unmapped_roots = unmapped.len();
+        out.audit.steam_unmapped_root_apps = unmapped.values().flatten().collect::<BTreeSet<_>>().len();
+
+        out
+    }
+}
+
+pub fn save_quality_report(
+    manifest: &Manifest,
+    overrides: &ManifestOverride,
+    wiki_cache: &WikiCache,
+    wiki_paths: &BTreeMap<String, Vec<WikiPath>>,
+    steam_cache: &SteamCache,
+) {
+    let Ok(gog_cache) = GogCache::load() else {
+        return;
+    };
+    let report = QualityReport::new(manifest, overrides, wiki_cache, wiki_paths, steam_cache, &gog_cache);
+    _ = std::fs::write(
+        format!("{}/data/quality.json", repo()),
+        serde_json::to_string_pretty(&report).unwrap() + "\n",
+    );
+}
@@ -1,20 +1,21 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::path::BROAD_PATHS;
+
 pub fn normalize(path: &str) -> String {
     let mut path = path.trim().trim_end_matches(['/', '\\']).replace('\\', "/");
 
     static CONSECUTIVE_SLASHES: Lazy<Regex> = Lazy::new(|| Regex::new(r"/{2,}").unwrap());
-    static UNNECESSARY_DOUBLE_STAR_1: Lazy<Regex> = Lazy::new(|| Regex::new(r"([^/*])\*{2,}").unwrap());
-    static UNNECESSARY_DOUBLE_STAR_2: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*{2,}([^/*])").unwrap());
+    // Unlike file paths, registry paths don't support `**` for recursion.
+    static DOUBLE_STAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*{2,}").unwrap());
     static ENDING_WILDCARD: Lazy<Regex> = Lazy::new(|| Regex::new(r"(/\*)+$").unwrap());
     static ENDING_DOT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(/\.)$").unwrap());
     static INTERMEDIATE_DOT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(/\./)").unwrap());
 
     for (pattern, replacement) in [
         (&CONSECUTIVE_SLASHES, "/"),
-        (&UNNECESSARY_DOUBLE_STAR_1, "${1}*"),
-        (&UNNECESSARY_DOUBLE_STAR_2, "*${1}"),
+        (&DOUBLE_STAR, "*"),
         (&ENDING_WILDCARD, ""),
         (&ENDING_DOT, ""),
         (&INTERMEDIATE_DOT, "/"),
@@ -25,7 +26,7 @@ pub fn normalize(path: &str) -> String {
     path
 }
 
-fn too_broad(path: &str) -> bool {
+pub fn too_broad(path: &str) -> bool {
     let path = path.to_lowercase();
 
     let valid = &["hkey_current_user", "hkey_local_machine"];
@@ -33,15 +34,9 @@ fn too_broad(path: &str) -> bool {
         return true;
     }
 
-    for item in [
-        "hkey_current_user",
-        "hkey_current_user/software",
-        "hkey_current_user/software/wow6432node",
-        "hkey_local_machine",
-        "hkey_local_machine/software",
-        "hkey_local_machine/software/wow6432node",
-    ] {
-        if path == item {
+    for item in &BROAD_PATHS.registry {
+        let item = item.to_lowercase();
+        if path == item || path.starts_with(&format!("{item}/*")) {
             return true;
         }
     }
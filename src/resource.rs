@@ -1,16 +1,240 @@
-use crate::REPO;
+use crate::{repo, should_save, use_sidecar};
 
 pub type AnyError = Box<dyn std::error::Error>;
 
+/// Default for how often to save during a long refresh, regardless of how many entries were processed.
+pub const AUTOSAVE_MINUTES: u64 = 5;
+
+/// Decides when to save progress during a long refresh,
+/// either after some number of entries or after some amount of time.
+#[derive(Debug)]
+pub struct Autosave {
+    interval: u32,
+    period: std::time::Duration,
+    processed: u32,
+    last: std::time::Instant,
+}
+
+impl Autosave {
+    pub fn new(interval: u32, minutes: u64) -> Self {
+        Self {
+            interval,
+            period: std::time::Duration::from_secs(minutes * 60),
+            processed: 0,
+            last: std::time::Instant::now(),
+        }
+    }
+
+    /// Record a processed entry and check if it's time to save.
+    pub fn tick(&mut self) -> bool {
+        self.processed += 1;
+
+        let due =
+            (self.interval > 0 && self.processed.is_multiple_of(self.interval)) || self.last.elapsed() >= self.period;
+        if due {
+            self.last = std::time::Instant::now();
+        }
+        due
+    }
+
+    pub fn processed(&self) -> u32 {
+        self.processed
+    }
+}
+
+/// Groups of data files that can be saved separately (see `--save-only`),
+/// so that each phase of an import can be committed on its own.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaveTarget {
+    /// The wiki caches and the reports derived only from them.
+    Wiki,
+    /// The Steam cache.
+    Steam,
+    /// The manifest and the reports derived from it.
+    Manifest,
+}
+
+/// How to lay out YAML when saving a resource file.
+/// The default matches `serde_yaml`, so adopting a style only changes what it asks for.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct YamlStyle {
+    /// Write collections of scalars inline (e.g., `[a, b]` or `{a: 1}`)
+    /// when they fit in this many characters, so that small changes only touch one line.
+    /// Zero disables this.
+    pub flow_width: usize,
+    /// Sort mapping keys instead of using the order from the data types.
+    pub sort_keys: bool,
+}
+
+impl YamlStyle {
+    /// For the big generated files, so that routine regenerations produce small diffs.
+    pub const COMPACT: Self = Self {
+        flow_width: 80,
+        sort_keys: true,
+    };
+}
+
+/// Serialize data as YAML with the given style.
+/// Scalars are quoted the same way as `serde_yaml`, regardless of the style.
+pub fn to_yaml<T: serde::Serialize>(data: &T, style: YamlStyle) -> String {
+    let value = serde_yaml::to_value(data).unwrap();
+
+    let mut emitter = YamlEmitter {
+        out: "---\n".to_string(),
+        level: -1,
+        style,
+    };
+    emitter.node(&value);
+    emitter.out.push('\n');
+    emitter.out
+}
+
+struct YamlEmitter {
+    out: String,
+    level: isize,
+    style: YamlStyle,
+}
+
+impl YamlEmitter {
+    fn indent(&mut self) {
+        for _ in 0..self.level.max(0) {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn node(&mut self, value: &serde_yaml::Value) {
+        match value {
+            serde_yaml::Value::Sequence(items) => self.sequence(items),
+            serde_yaml::Value::Mapping(mapping) => self.mapping(mapping),
+            _ => self.out.push_str(&scalar(value)),
+        }
+    }
+
+    fn sequence(&mut self, items: &[serde_yaml::Value]) {
+        if items.is_empty() {
+            self.out.push_str("[]");
+            return;
+        }
+
+        self.level += 1;
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+                self.indent();
+            }
+            self.out.push('-');
+            self.value(true, item);
+        }
+        self.level -= 1;
+    }
+
+    fn mapping(&mut self, mapping: &serde_yaml::Mapping) {
+        if mapping.is_empty() {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.level += 1;
+        for (i, (key, value)) in self.entries(mapping).into_iter().enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+                self.indent();
+            }
+            self.out.push_str(&scalar(key));
+            self.out.push(':');
+            self.value(false, value);
+        }
+        self.level -= 1;
+    }
+
+    /// Write a value after `:` or `-`.
+    /// Nested collections go on the next line, except for sequence items.
+    fn value(&mut self, inline: bool, value: &serde_yaml::Value) {
+        let empty = match value {
+            serde_yaml::Value::Sequence(items) => items.is_empty(),
+            serde_yaml::Value::Mapping(mapping) => mapping.is_empty(),
+            _ => {
+                self.out.push(' ');
+                self.node(value);
+                return;
+            }
+        };
+
+        if let Some(flow) = self.flow(value) {
+            self.out.push(' ');
+            self.out.push_str(&flow);
+        } else if inline || empty {
+            self.out.push(' ');
+            self.node(value);
+        } else {
+            self.out.push('\n');
+            self.level += 1;
+            self.indent();
+            self.level -= 1;
+            self.node(value);
+        }
+    }
+
+    fn entries<'a>(&self, mapping: &'a serde_yaml::Mapping) -> Vec<(&'a serde_yaml::Value, &'a serde_yaml::Value)> {
+        let mut entries: Vec<_> = mapping.iter().collect();
+        if self.style.sort_keys {
+            // Compare the keys themselves, so that numbers and strings sort the same way as in a `BTreeMap`.
+            entries.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        }
+        entries
+    }
+
+    /// Render a non-empty collection of scalars inline, if it fits.
+    fn flow(&self, value: &serde_yaml::Value) -> Option<String> {
+        let is_scalar =
+            |x: &serde_yaml::Value| !matches!(x, serde_yaml::Value::Sequence(_) | serde_yaml::Value::Mapping(_));
+
+        let flow = match value {
+            serde_yaml::Value::Sequence(items) if !items.is_empty() && items.iter().all(is_scalar) => {
+                format!("[{}]", items.iter().map(scalar).collect::<Vec<_>>().join(", "))
+            }
+            serde_yaml::Value::Mapping(mapping) if !mapping.is_empty() && mapping.iter().all(|(_, x)| is_scalar(x)) => {
+                let entries: Vec<_> = self
+                    .entries(mapping)
+                    .into_iter()
+                    .map(|(key, value)| format!("{}: {}", scalar(key), scalar(value)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            _ => return None,
+        };
+
+        (flow.len() <= self.style.flow_width).then_some(flow)
+    }
+}
+
+/// Plain scalars never contain flow indicators like `,` or `[`,
+/// since `serde_yaml` quotes those, so this works in both block and flow collections.
+fn scalar(value: &serde_yaml::Value) -> String {
+    let raw = serde_yaml::to_string(value).unwrap();
+    let raw = raw.strip_prefix("---").unwrap_or(&raw);
+    let raw = raw.strip_prefix(['\n', ' ']).unwrap_or(raw);
+    raw.trim_end_matches('\n').to_string()
+}
+
 pub trait ResourceFile
 where
     Self: Default + serde::Serialize + serde::de::DeserializeOwned,
 {
     const FILE_NAME: &'static str;
+    const YAML_STYLE: YamlStyle = YamlStyle {
+        flow_width: 0,
+        sort_keys: false,
+    };
+    /// Whether this file is big enough to benefit from a binary sidecar (see `--sidecar`).
+    const SIDECAR: bool = false;
+    /// Which group this file belongs to for `--save-only`.
+    /// Files without a group are always saved.
+    const SAVE_TARGET: Option<SaveTarget> = None;
 
     fn path() -> std::path::PathBuf {
         let mut path = std::path::PathBuf::new();
-        path.push(REPO);
+        path.push(repo());
         path.push(Self::FILE_NAME);
         path
     }
@@ -33,8 +257,17 @@ where
         if !path.exists() {
             return Ok(Self::default().initialize());
         }
+        if Self::SIDECAR && use_sidecar() && *path == Self::path() {
+            if let Some(data) = Sidecar::load(path) {
+                return Ok(ResourceFile::migrate(data));
+            }
+        }
         let content = Self::load_raw(path)?;
-        Self::load_from_string(&content)
+        let data = Self::load_from_string(&content)?;
+        if Self::SIDECAR && use_sidecar() && *path == Self::path() {
+            Sidecar::save(path, &data);
+        }
+        Ok(data)
     }
 
     fn load_raw(path: &std::path::PathBuf) -> Result<String, AnyError> {
@@ -46,11 +279,15 @@ where
     }
 
     fn serialize(&self) -> String {
-        serde_yaml::to_string(&self).unwrap()
+        to_yaml(self, Self::YAML_STYLE)
     }
 
     fn save(&self) {
-        let new_content = serde_yaml::to_string(&self).unwrap();
+        if Self::SAVE_TARGET.is_some_and(|x| !should_save(x)) {
+            return;
+        }
+
+        let new_content = ResourceFile::serialize(self);
 
         if let Ok(old_content) = Self::load_raw(&Self::path()) {
             if old_content == new_content {
@@ -59,5 +296,99 @@ where
         }
 
         let _ = std::fs::write(Self::path(), new_content.as_bytes());
+        if Self::SIDECAR && use_sidecar() {
+            Sidecar::save(&Self::path(), self);
+        }
+    }
+}
+
+/// A binary copy of a resource file, which is only used while the YAML file is unchanged.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Sidecar<T> {
+    /// Size and modification time of the YAML file when this copy was made.
+    source: (u64, u128),
+    data: T,
+}
+
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Sidecar<T> {
+    fn path(source: &std::path::Path) -> std::path::PathBuf {
+        let mut path = std::path::PathBuf::new();
+        path.push(repo());
+        path.push("tmp");
+        path.push(source.file_stem().unwrap_or_default());
+        path.set_extension("cbor");
+        path
+    }
+
+    fn fingerprint(source: &std::path::Path) -> Option<(u64, u128)> {
+        let metadata = std::fs::metadata(source).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some((metadata.len(), modified))
+    }
+
+    fn load(source: &std::path::Path) -> Option<T> {
+        let fingerprint = Self::fingerprint(source)?;
+        let file = std::fs::File::open(Self::path(source)).ok()?;
+        let sidecar: Self = ciborium::from_reader(std::io::BufReader::new(file)).ok()?;
+        (sidecar.source == fingerprint).then_some(sidecar.data)
+    }
+
+    fn save(source: &std::path::Path, data: &T) {
+        let Some(fingerprint) = Self::fingerprint(source) else {
+            return;
+        };
+        let path = Self::path(source);
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let Ok(file) = std::fs::File::create(&path) else {
+            return;
+        };
+
+        let sidecar = Sidecar {
+            source: fingerprint,
+            data,
+        };
+        if ciborium::into_writer(&sidecar, std::io::BufWriter::new(file)).is_err() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_style_defaults_to_serde_yaml() {
+        let data = serde_yaml::from_str::<serde_yaml::Value>(
+            "a:\n  - 1\n  - x: \"y: z\"\n    w: []\nb: {}\nc:\n  d: \"yes\"\n  e: ~\n",
+        )
+        .unwrap();
+        assert_eq!(
+            serde_yaml::to_string(&data).unwrap(),
+            to_yaml(&data, YamlStyle::default())
+        );
+    }
+
+    #[test]
+    fn yaml_style_can_use_flow_collections() {
+        let data = serde_yaml::from_str::<serde_yaml::Value>(
+            "b:\n  tags: [save, config]\n  when:\n    - os: windows\n      store: steam\na: 1\n10: x\n9: y\n\"a b\": z\n",
+        )
+        .unwrap();
+        let style = YamlStyle {
+            flow_width: 40,
+            sort_keys: true,
+        };
+        assert_eq!(
+            "---\n9: y\n10: x\na: 1\na b: z\nb:\n  tags: [save, config]\n  when:\n    - {os: windows, store: steam}\n",
+            to_yaml(&data, style)
+        );
     }
 }
@@ -1,32 +1,375 @@
-use crate::{manifest::Manifest, resource::ResourceFile, Error, REPO};
+use std::collections::BTreeMap;
 
-pub fn validate_manifest(manifest: &Manifest) -> Result<(), Error> {
-    let manifest: serde_json::Value = serde_yaml::from_str(&manifest.serialize()).unwrap();
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+
+use crate::{
+    manifest::{Game, Manifest},
+    repo,
+    resource::{ResourceFile, SaveTarget},
+    Error,
+};
+
+/// The schema revision that the manifest is generated and validated for.
+pub static VERSION: Lazy<u64> = Lazy::new(|| {
+    let schema: serde_yaml::Value = serde_yaml::from_str(&read_data("schema.yaml")).unwrap();
+    schema["version"].as_u64().unwrap_or_default()
+});
+
+/// Details about the manifest that tools can read before parsing it.
+/// The top level of the manifest is a map of game titles,
+/// so these can't be normal fields in the manifest itself.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ManifestMeta {
+    /// The schema revision that the manifest was generated for.
+    pub schema_version: u64,
+}
 
-    let normal: serde_json::Value = serde_yaml::from_str(&read_data("schema.yaml")).unwrap();
-    let strict: serde_json::Value = serde_yaml::from_str(&read_data("schema.strict.yaml")).unwrap();
+impl ResourceFile for ManifestMeta {
+    const FILE_NAME: &'static str = "data/manifest-meta.yaml";
+    const SAVE_TARGET: Option<SaveTarget> = Some(SaveTarget::Manifest);
+}
 
-    for schema in [normal, strict] {
-        if !check(&schema, &manifest) {
-            return Err(Error::ManifestSchema);
+impl ManifestMeta {
+    pub fn current() -> Self {
+        Self {
+            schema_version: *VERSION,
         }
     }
+}
+
+/// A fingerprint of the schema files for each schema version,
+/// so that we notice when the schema changes without a new version.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SchemaVersions(pub BTreeMap<u64, String>);
 
-    Ok(())
+impl ResourceFile for SchemaVersions {
+    const FILE_NAME: &'static str = "data/schema-versions.yaml";
 }
 
-fn read_data(file: &str) -> String {
-    std::fs::read_to_string(format!("{}/data/{}", REPO, file)).unwrap()
+/// Fingerprint the current schema files, ignoring formatting, comments, and the version itself.
+pub fn fingerprint() -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for file in ["schema.yaml", "schema.strict.yaml"] {
+        let mut schema: serde_json::Value = serde_yaml::from_str(&read_data(file)).unwrap();
+        if let Some(schema) = schema.as_object_mut() {
+            schema.remove("version");
+        }
+        // FNV-1a, since we only need to detect changes.
+        for byte in canonical(&schema).bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Serialize JSON with sorted keys, so that reordering the schema doesn't change the fingerprint.
+fn canonical(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => format!(
+            "{{{}}}",
+            map.iter()
+                .sorted_by_key(|(key, _)| *key)
+                .map(|(key, value)| format!("{}:{}", serde_json::Value::from(key.as_str()), canonical(value)))
+                .join(",")
+        ),
+        serde_json::Value::Array(items) => format!("[{}]", items.iter().map(canonical).join(",")),
+        _ => value.to_string(),
+    }
+}
+
+/// Check that the schema files are versioned consistently.
+/// Returns a description of the problem, if any.
+pub fn version_problem() -> Option<String> {
+    let strict: serde_yaml::Value = serde_yaml::from_str(&read_data("schema.strict.yaml")).unwrap();
+    if strict["version"].as_u64() != Some(*VERSION) {
+        return Some(format!(
+            "schema.strict.yaml is not at the same version as schema.yaml ({})",
+            *VERSION
+        ));
+    }
+
+    let current = fingerprint();
+    match SchemaVersions::load() {
+        Ok(versions) => match versions.0.get(&*VERSION) {
+            Some(recorded) if *recorded == current => None,
+            Some(_) => Some(format!(
+                "the schema changed since version {} was recorded in {}; increment the version and record `{}: \"{}\"`",
+                *VERSION,
+                SchemaVersions::FILE_NAME,
+                *VERSION + 1,
+                current
+            )),
+            None => Some(format!(
+                "schema version {} is not recorded in {}; add `{}: \"{}\"`",
+                *VERSION,
+                SchemaVersions::FILE_NAME,
+                *VERSION,
+                current
+            )),
+        },
+        Err(e) => Some(format!("{}: {}", SchemaVersions::FILE_NAME, e)),
+    }
 }
 
-fn check(schema: &serde_json::Value, instance: &serde_json::Value) -> bool {
-    let mut valid = true;
-    let compiled = jsonschema::JSONSchema::compile(schema).unwrap();
-    if let Err(errors) = compiled.validate(instance) {
-        valid = false;
+/// The schemas describe the whole manifest as a map of games,
+/// so we compile the schema for a single game and check each one separately.
+static SCHEMAS: Lazy<Vec<jsonschema::JSONSchema>> = Lazy::new(|| {
+    ["schema.yaml", "schema.strict.yaml"]
+        .into_iter()
+        .map(|file| {
+            let schema: serde_json::Value = serde_yaml::from_str(&read_data(file)).unwrap();
+            jsonschema::JSONSchema::compile(&game_schema(schema)).unwrap()
+        })
+        .collect()
+});
+
+pub fn validate_manifest(manifest: &Manifest) -> Result<(), Error> {
+    if let Some(problem) = version_problem() {
+        println!("Schema error: {}", problem);
+        return Err(Error::ManifestSchema);
+    }
+
+    let games: Vec<_> = manifest.0.iter().collect();
+    let threads = std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1);
+    let chunk_size = games.len().div_ceil(threads).max(1);
+
+    let failures: Vec<(String, Vec<String>)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = games
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|(title, game)| {
+                            let errors = check(game);
+                            (!errors.is_empty()).then(|| (title.to_string(), errors))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles.into_iter().flat_map(|x| x.join().unwrap()).collect()
+    });
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    for (title, errors) in failures {
         for error in errors {
-            println!("Schema error: {}  |  {}", error, error.instance_path);
+            println!("Schema error: {}  |  {}", title, error);
+        }
+    }
+    Err(Error::ManifestSchema)
+}
+
+fn read_data(file: &str) -> String {
+    std::fs::read_to_string(format!("{}/data/{}", repo(), file)).unwrap()
+}
+
+fn game_schema(mut schema: serde_json::Value) -> serde_json::Value {
+    let mut game = schema["additionalProperties"].take();
+    game["definitions"] = schema["definitions"].take();
+    game
+}
+
+pub fn check(game: &Game) -> Vec<String> {
+    let instance = serde_json::to_value(game).unwrap();
+
+    let mut out = vec![];
+    for schema in SCHEMAS.iter() {
+        if let Err(errors) = schema.validate(&instance) {
+            for error in errors {
+                out.push(format!("{}  |  {}", error, error.instance_path));
+            }
         }
     }
-    valid
+    out
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum TypeLanguage {
+    /// TypeScript
+    Ts,
+    /// Go
+    Go,
+}
+
+/// Generate type definitions for the manifest format from `schema.yaml`,
+/// which is the schema that tools should implement and that the manifest is validated against.
+pub fn generate_types(language: TypeLanguage) -> String {
+    let schema: serde_json::Value = serde_yaml::from_str(&read_data("schema.yaml")).unwrap();
+
+    let mut generator = TypeGenerator {
+        language,
+        blocks: vec![],
+    };
+    let manifest = match language {
+        TypeLanguage::Ts => "export type Manifest = Record<string, Game>;\n",
+        TypeLanguage::Go => "type Manifest map[string]Game\n",
+    };
+    generator.blocks.push(manifest.to_string());
+    generator.named("Game", &schema["additionalProperties"], None);
+    if let Some(definitions) = schema["definitions"].as_object() {
+        for (name, definition) in definitions {
+            generator.named(name, definition, definition["description"].as_str());
+        }
+    }
+
+    let header = match language {
+        TypeLanguage::Ts => "// Generated from the ludusavi-manifest schema. Do not edit.\n".to_string(),
+        TypeLanguage::Go => {
+            "// Code generated from the ludusavi-manifest schema. DO NOT EDIT.\n\npackage manifest\n".to_string()
+        }
+    };
+    std::iter::once(header).chain(generator.blocks).join("\n")
+}
+
+struct TypeGenerator {
+    language: TypeLanguage,
+    blocks: Vec<String>,
+}
+
+impl TypeGenerator {
+    /// Add a named type and return its name.
+    fn named(&mut self, name: &str, schema: &serde_json::Value, description: Option<&str>) -> String {
+        // Reserve a spot so that types are listed before the ones nested inside of them.
+        let index = self.blocks.len();
+        self.blocks.push(String::new());
+
+        let mut block = comment(description, "", self.language);
+        match schema["properties"].as_object() {
+            Some(properties) if schema["type"] == "object" => {
+                let fields: Vec<_> = properties
+                    .iter()
+                    .map(|(key, property)| {
+                        let hint = format!("{}{}", name, pascal_case(key));
+                        let field_type = self.expr(property, &hint);
+                        (key, property["description"].as_str(), field_type, is_struct(property))
+                    })
+                    .collect();
+
+                match self.language {
+                    TypeLanguage::Ts => {
+                        block += &format!("export interface {} {{\n", name);
+                        for (key, description, field_type, _) in fields {
+                            block += &comment(description, "  ", self.language);
+                            block += &format!("  {}?: {};\n", key, field_type);
+                        }
+                        block += "}\n";
+                    }
+                    TypeLanguage::Go => {
+                        block += &format!("type {} struct {{\n", name);
+                        for (key, description, field_type, is_struct) in fields {
+                            block += &comment(description, "\t", self.language);
+                            block += &format!(
+                                "\t{} {}{} `json:\"{key},omitempty\" yaml:\"{key},omitempty\"`\n",
+                                pascal_case(key),
+                                if is_struct { "*" } else { "" },
+                                field_type,
+                            );
+                        }
+                        block += "}\n";
+                    }
+                }
+            }
+            _ => {
+                let alias = self.expr_inner(schema, name);
+                match self.language {
+                    TypeLanguage::Ts => block += &format!("export type {} = {};\n", name, alias),
+                    TypeLanguage::Go => block += &format!("type {} {}\n", name, alias),
+                }
+            }
+        }
+
+        self.blocks[index] = block;
+        name.to_string()
+    }
+
+    /// Get the type expression for a schema, adding named types for any nested objects.
+    fn expr(&mut self, schema: &serde_json::Value, hint: &str) -> String {
+        if is_struct(schema) {
+            self.named(hint, schema, None)
+        } else {
+            self.expr_inner(schema, hint)
+        }
+    }
+
+    fn expr_inner(&mut self, schema: &serde_json::Value, hint: &str) -> String {
+        if let Some(reference) = schema["$ref"].as_str() {
+            return reference.trim_start_matches("#/definitions/").to_string();
+        }
+
+        let ts = self.language == TypeLanguage::Ts;
+        match schema["type"].as_str() {
+            Some("string") => "string".to_string(),
+            Some("integer") => if ts { "number" } else { "int64" }.to_string(),
+            Some("boolean") => if ts { "boolean" } else { "bool" }.to_string(),
+            Some("array") => {
+                let item = self.expr(&schema["items"], &format!("{}Item", hint));
+                if ts {
+                    format!("{}[]", item)
+                } else {
+                    format!("[]{}", item)
+                }
+            }
+            Some("object") if schema["additionalProperties"].is_object() => {
+                let value = self.expr(&schema["additionalProperties"], &format!("{}Entry", hint));
+                if ts {
+                    format!("Record<string, {}>", value)
+                } else {
+                    format!("map[string]{}", value)
+                }
+            }
+            _ => if ts {
+                "Record<string, unknown>"
+            } else {
+                "map[string]any"
+            }
+            .to_string(),
+        }
+    }
+}
+
+fn is_struct(schema: &serde_json::Value) -> bool {
+    schema["type"] == "object" && schema["properties"].is_object()
+}
+
+fn pascal_case(key: &str) -> String {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn comment(description: Option<&str>, indent: &str, language: TypeLanguage) -> String {
+    let Some(description) = description else {
+        return String::new();
+    };
+    let lines: Vec<_> = description.trim().lines().collect();
+    match language {
+        TypeLanguage::Ts => {
+            let mut out = format!("{indent}/**\n");
+            for line in lines {
+                out += &format!("{indent} * {line}\n");
+            }
+            out + &format!("{indent} */\n")
+        }
+        TypeLanguage::Go => lines.iter().map(|line| format!("{indent}// {line}\n")).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_changes_increment_the_version() {
+        assert_eq!(None, version_problem());
+    }
 }
@@ -0,0 +1,107 @@
+use crate::{
+    cli::{self, OutputFormat},
+    manifest::Game,
+    resource::ResourceFile,
+    wiki::{PrimaryIds, WikiCacheEntry},
+};
+
+/// Articles with known-good output, covering the trickier parts of path extraction.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SelfTestCorpus(pub Vec<SelfTestCase>);
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SelfTestCase {
+    pub title: String,
+    pub wikitext: String,
+    /// The manifest entry that should be generated from the wikitext alone.
+    pub expected: Game,
+}
+
+impl ResourceFile for SelfTestCorpus {
+    const FILE_NAME: &'static str = "data/selftest.yaml";
+}
+
+/// Run the wiki extraction on each case in the corpus and compare against the expected entry.
+/// Returns false if any case doesn't match.
+pub fn run() -> bool {
+    let corpus = match SelfTestCorpus::load() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}: {}", SelfTestCorpus::FILE_NAME, e);
+            return false;
+        }
+    };
+
+    let mut failures = vec![];
+    for case in &corpus.0 {
+        let cache = WikiCacheEntry::from_wikitext(&case.wikitext, case.title.clone());
+
+        let mut actual = Game::default();
+        actual.integrate_wiki(&cache, &case.title, &PrimaryIds::default(), &Default::default());
+        actual.integrate_ignore_hints();
+
+        if actual == case.expected {
+            continue;
+        }
+
+        failures.push(SelfTestFailure {
+            title: &case.title,
+            expected: &case.expected,
+            actual,
+        });
+    }
+
+    let report = SelfTestReport {
+        passed: corpus.0.len() - failures.len(),
+        total: corpus.0.len(),
+        failures,
+    };
+    if OutputFormat::current() == OutputFormat::Table {
+        // The entries are too nested for columns, so they're shown as indented YAML instead.
+        report.print();
+    } else {
+        cli::print_report(&report);
+    }
+    report.failures.is_empty()
+}
+
+#[derive(serde::Serialize)]
+struct SelfTestReport<'a> {
+    passed: usize,
+    total: usize,
+    failures: Vec<SelfTestFailure<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct SelfTestFailure<'a> {
+    title: &'a str,
+    expected: &'a Game,
+    actual: Game,
+}
+
+impl SelfTestReport<'_> {
+    fn print(&self) {
+        for failure in &self.failures {
+            println!("FAILED: {}", failure.title);
+            println!("  Expected:");
+            for line in serde_yaml::to_string(failure.expected)
+                .unwrap_or_default()
+                .lines()
+                .skip(1)
+            {
+                println!("    {}", line);
+            }
+            println!("  Actual:");
+            for line in serde_yaml::to_string(&failure.actual)
+                .unwrap_or_default()
+                .lines()
+                .skip(1)
+            {
+                println!("    {}", line);
+            }
+        }
+
+        println!("{} of {} cases passed", self.passed, self.total);
+    }
+}
@@ -1,19 +1,22 @@
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     process::Command,
 };
 
 use itertools::Itertools;
+use once_cell::sync::Lazy;
 
 use crate::{
-    manifest::{placeholder, Os},
-    resource::ResourceFile,
+    budget, find_decoding_culprit,
+    manifest::{placeholder, ManifestOverride, Os},
+    notify, repo,
+    resource::{Autosave, ResourceFile, SaveTarget, YamlStyle},
     should_cancel,
     wiki::WikiCache,
-    Error, State, REPO,
+    Error, State,
 };
 
-const SAVE_INTERVAL: u32 = 250;
+pub const SAVE_INTERVAL: u32 = 250;
 const CHUNK_SIZE: usize = 25;
 
 #[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
@@ -21,6 +24,16 @@ pub struct SteamCache(pub BTreeMap<u32, SteamCacheEntry>);
 
 impl ResourceFile for SteamCache {
     const FILE_NAME: &'static str = "data/steam-game-cache.yaml";
+    const YAML_STYLE: YamlStyle = YamlStyle::COMPACT;
+    const SIDECAR: bool = true;
+    const SAVE_TARGET: Option<SaveTarget> = Some(SaveTarget::Steam);
+
+    fn migrate(mut self) -> Self {
+        if self.0.remove(&0).is_some() {
+            eprintln!("[Steam] removing entry for invalid app ID 0");
+        }
+        self
+    }
 }
 
 impl SteamCache {
@@ -30,8 +43,8 @@ impl SteamCache {
         app_ids: Option<Vec<u32>>,
         limit: Option<usize>,
         from: Option<u32>,
+        mut autosave: Autosave,
     ) -> Result<(), Error> {
-        let mut i = 0;
         let app_ids: Vec<_> = app_ids.unwrap_or_else(|| {
             self.0
                 .iter()
@@ -43,55 +56,139 @@ impl SteamCache {
         });
 
         for app_ids in app_ids.chunks(CHUNK_SIZE) {
-            if should_cancel() {
+            if should_cancel() || budget::exhausted(budget::Api::Steam) {
                 break;
             }
 
-            let info = ProductInfo::fetch(app_ids)?;
-            for app_id in app_ids {
-                let latest = match SteamCacheEntry::parse_app(*app_id, &info) {
-                    Ok(x) => x,
-                    Err(e) => {
-                        println!("Steam: {app_id} - failed");
-                        return Err(e);
-                    }
-                };
-                self.0.insert(
-                    *app_id,
-                    latest.unwrap_or_else(|| SteamCacheEntry {
+            self.refresh_batch(app_ids, &mut autosave, &ProductInfo::fetch)?;
+        }
+
+        Ok(())
+    }
+
+    /// If a batch fails because of one of its apps, we split it in half and try again,
+    /// so that one problematic app doesn't block the rest of the queue.
+    /// Any other failure stops the refresh and leaves the remaining apps outdated.
+    fn refresh_batch(
+        &mut self,
+        app_ids: &[u32],
+        autosave: &mut Autosave,
+        fetch: &impl Fn(&[u32]) -> Result<ProductInfo, Error>,
+    ) -> Result<(), Error> {
+        // Retries of a failed batch can add up, so they have to respect these as well.
+        // Anything that we skip stays outdated for the next run.
+        if app_ids.is_empty() || should_cancel() || budget::exhausted(budget::Api::Steam) {
+            return Ok(());
+        }
+
+        let info = match fetch(app_ids) {
+            Ok(x) => x,
+            Err(e @ Error::SteamApp { app_id, .. }) if app_ids.len() > 1 && app_ids.contains(&app_id) => {
+                // We know which app broke the batch, so we only need to retry the others.
+                println!("Steam: batch failed, retrying without the responsible app - {e}");
+                notify::failure(&e);
+                self.mark_failed(app_id);
+                let others: Vec<_> = app_ids.iter().copied().filter(|x| *x != app_id).collect();
+                return self.refresh_batch(&others, autosave, fetch);
+            }
+            // If Steam or the script is unavailable, retrying each app would only fail the same way.
+            Err(e) if !matches!(e, Error::SteamApp { .. } | Error::SteamProductInfoDecoding(_)) => {
+                return Err(e);
+            }
+            Err(e) if app_ids.len() > 1 => {
+                println!("Steam: batch failed, retrying in smaller batches - {e}");
+                let (first, second) = app_ids.split_at(app_ids.len() / 2);
+                self.refresh_batch(first, autosave, fetch)?;
+                return self.refresh_batch(second, autosave, fetch);
+            }
+            Err(e) => {
+                let e = e.for_steam_app(app_ids[0]);
+                println!("Steam: failed - {e}");
+                notify::failure(&e);
+                self.mark_failed(app_ids[0]);
+                return Ok(());
+            }
+        };
+
+        // Even if we've been cancelled by now, we keep the results that we already fetched.
+        for app_id in app_ids {
+            match SteamCacheEntry::parse_app(*app_id, &info) {
+                Ok(latest) => {
+                    let mut latest = latest.unwrap_or_else(|| SteamCacheEntry {
                         state: State::Handled,
                         ..Default::default()
-                    }),
-                );
-
-                i += 1;
-                if i % SAVE_INTERVAL == 0 {
-                    self.save();
-                    println!("\n:: saved ({i})\n");
+                    });
+                    if let Some(previous) = self.0.get(app_id) {
+                        latest.remember_install_dirs(previous);
+                    }
+                    self.0.insert(*app_id, latest);
+                }
+                Err(e) => {
+                    let e = e.for_steam_app(*app_id);
+                    println!("Steam: failed - {e}");
+                    notify::failure(&e);
+                    self.mark_failed(*app_id);
                 }
             }
+
+            if autosave.tick() {
+                self.save();
+                println!("\n:: saved ({})\n", autosave.processed());
+            }
         }
 
         Ok(())
     }
 
-    pub fn transition_states_from(&mut self, wiki_cache: &mut WikiCache) {
+    /// We keep any old data, but we don't retry the app until it's flagged again.
+    fn mark_failed(&mut self, app_id: u32) {
+        let entry = self.0.entry(app_id).or_default();
+        entry.irregular = true;
+        entry.state = State::Handled;
+    }
+
+    /// Map each full game's app ID to the app IDs of its demos.
+    pub fn demos(&self) -> HashMap<u32, BTreeSet<u32>> {
+        let mut out = HashMap::<u32, BTreeSet<u32>>::new();
+        for (app_id, info) in &self.0 {
+            if let Some(full) = info.demo_of {
+                out.entry(full).or_default().insert(*app_id);
+            }
+        }
+        out
+    }
+
+    /// Flag Steam entries for refresh after wiki updates,
+    /// but only if the wiki's Steam ID changed or we don't have the app's data yet,
+    /// since most wiki edits don't affect the Steam data.
+    /// Co-primary apps from the overrides are merged into the manifest too,
+    /// and side apps need a name for maintainers to check them,
+    /// so we also fetch any of those that we don't have yet.
+    pub fn transition_states_from(&mut self, wiki_cache: &mut WikiCache, overrides: &ManifestOverride) {
         for wiki in wiki_cache.0.values_mut() {
             if wiki.state == State::Updated {
                 if let Some(id) = wiki.steam {
-                    self.0
-                        .entry(id)
-                        .and_modify(|x| {
-                            x.state = State::Outdated;
-                        })
-                        .or_insert(SteamCacheEntry {
-                            state: State::Outdated,
-                            ..Default::default()
-                        });
+                    let entry = self.0.entry(id).or_insert(SteamCacheEntry {
+                        state: State::Outdated,
+                        ..Default::default()
+                    });
+                    if wiki.steam_changed {
+                        entry.state = State::Outdated;
+                    }
                 }
+                wiki.steam_changed = false;
                 wiki.state = State::Handled;
             }
         }
+
+        let side = wiki_cache.0.values().flat_map(|x| &x.steam_side);
+        let co_primary = overrides.0.values().flat_map(|x| &x.co_primary_steam);
+        for id in side.chain(co_primary) {
+            self.0.entry(*id).or_insert(SteamCacheEntry {
+                state: State::Outdated,
+                ..Default::default()
+            });
+        }
     }
 }
 
@@ -104,10 +201,20 @@ pub struct SteamCacheEntry {
     pub irregular: bool,
     #[serde(skip_serializing_if = "Cloud::is_empty")]
     pub cloud: Cloud,
+    /// If this app is a demo, then this is the app ID of the full game.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub demo_of: Option<u32>,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub dlc: BTreeSet<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub install_dir: Option<String>,
+    /// Install folders from earlier releases, which users may still have.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub old_install_dirs: BTreeSet<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub launch: Vec<Launch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub name_localized: BTreeMap<String, String>,
 }
@@ -164,6 +271,23 @@ pub struct CloudTransform {
     pub replace: String,
 }
 
+impl CloudTransform {
+    /// Some replacements use environment variables or home shorthand,
+    /// which we can't express relative to the override's root.
+    pub fn is_supported(&self) -> bool {
+        !self.replace.contains('%') && !self.replace.starts_with('~')
+    }
+
+    /// An empty `find` replaces the whole path, and an empty `replace` deletes the match.
+    pub fn apply(&self, path: &str) -> String {
+        match (self.find.is_empty(), self.replace.is_empty()) {
+            (true, true) => path.to_string(),
+            (true, false) => self.replace.clone(),
+            (false, _) => path.replace(&self.find, &self.replace),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Launch {
@@ -181,7 +305,103 @@ pub struct Launch {
     pub workingdir: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchKind {
+    Game,
+    Benchmark,
+    CrashHandler,
+    Server,
+    Tool,
+    Vr,
+}
+
+impl LaunchKind {
+    /// Whether this is something other than the game itself,
+    /// which would mislead tools looking for the primary executable.
+    pub fn is_auxiliary(&self) -> bool {
+        *self != Self::Game
+    }
+}
+
+/// Split an executable name into lowercase words,
+/// at separators and at changes between lowercase, uppercase, and digits
+/// (e.g., `UnityCrashHandler64` becomes `unity`, `crash`, `handler`, `64`).
+fn executable_words(stem: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in stem.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous = None;
+            continue;
+        }
+        let boundary =
+            previous.is_some_and(|p| (p.is_lowercase() && c.is_uppercase()) || (p.is_numeric() != c.is_numeric()));
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+        previous = Some(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
 impl Launch {
+    /// Steam's declared type is trusted when it has one,
+    /// including `default` for the game itself.
+    /// Otherwise, we only go by whole words in the executable name,
+    /// since descriptions are free text that often mentions other things.
+    pub fn kind(&self) -> LaunchKind {
+        match self.r#type.as_deref().map(|x| x.to_lowercase()).as_deref() {
+            Some("default") => return LaunchKind::Game,
+            Some("benchmark") => return LaunchKind::Benchmark,
+            Some("server") => return LaunchKind::Server,
+            Some("config" | "editor") => return LaunchKind::Tool,
+            Some("vr" | "openvr" | "openxr" | "othervr" | "oculus") => return LaunchKind::Vr,
+            _ => {}
+        }
+        if self.config.vrmode.is_some() {
+            return LaunchKind::Vr;
+        }
+
+        let file = self
+            .executable
+            .as_deref()
+            .and_then(|x| x.rsplit(['/', '\\']).next())
+            .unwrap_or_default();
+        let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file);
+        let words = executable_words(stem);
+        let has = |word: &str| words.iter().any(|x| x == word);
+        let has_pair = |first: &str, second: &str| words.windows(2).any(|x| x[0] == first && x[1] == second);
+
+        if has("crashhandler")
+            || has("crashreporter")
+            || has("bugsplat")
+            || ["handler", "report", "reporter", "sender"]
+                .iter()
+                .any(|x| has_pair("crash", x))
+        {
+            LaunchKind::CrashHandler
+        } else if has("benchmark") {
+            LaunchKind::Benchmark
+        } else if has("dedicatedserver") || has_pair("dedicated", "server") || stem.to_lowercase().ends_with("_server")
+        {
+            LaunchKind::Server
+        } else if ["config", "configurator", "settings", "setup"].iter().any(|x| has(x)) {
+            LaunchKind::Tool
+        } else {
+            LaunchKind::Game
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.arguments.is_none()
             && self.config.is_empty()
@@ -203,11 +423,20 @@ pub struct LaunchConfig {
     pub oslist: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ownsdlc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steamdeck: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vrmode: Option<String>,
 }
 
 impl LaunchConfig {
     pub fn is_empty(&self) -> bool {
-        self.betakey.is_none() && self.osarch.is_none() && self.oslist.is_none() && self.ownsdlc.is_none()
+        self.betakey.is_none()
+            && self.osarch.is_none()
+            && self.oslist.is_none()
+            && self.ownsdlc.is_none()
+            && self.steamdeck.is_none()
+            && self.vrmode.is_none()
     }
 }
 
@@ -219,13 +448,28 @@ struct ProductInfo {
 impl ProductInfo {
     fn fetch(app_ids: &[u32]) -> Result<ProductInfo, Error> {
         println!("Steam batch: {}", app_ids.iter().join(", "));
+        budget::spend(budget::Api::Steam);
 
         let mut cmd = Command::new("python");
-        cmd.arg(format!("{}/scripts/get-steam-app-info.py", REPO));
+        cmd.arg(format!("{}/scripts/get-steam-app-info.py", repo()));
         for app_id in app_ids {
             cmd.arg(app_id.to_string());
         }
 
+        // Keep Ctrl+C from killing the script so that the in-flight batch can finish.
+        // We'll check for cancellation once it's done.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+            cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+        }
+
         let output = cmd.output()?;
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -234,14 +478,27 @@ impl ProductInfo {
         }
         let stdout = String::from_utf8_lossy(&output.stdout);
 
+        let raw = serde_json::from_str::<serde_json::Value>(&stdout).map_err(Error::SteamProductInfoDecoding)?;
+        let response = match serde_json::from_str::<product_info::Response>(&stdout) {
+            Ok(x) => x,
+            Err(e) => {
+                return Err(
+                    match find_decoding_culprit::<product_info::App>(&raw["apps"])
+                        .and_then(|(app_id, e)| Some((app_id.parse::<u32>().ok()?, e)))
+                    {
+                        Some((app_id, e)) => Error::SteamProductInfoDecoding(e).for_steam_app(app_id),
+                        None => Error::SteamProductInfoDecoding(e),
+                    },
+                );
+            }
+        };
+
         let mut info = ProductInfo {
-            response: serde_json::from_str::<product_info::Response>(&stdout)
-                .map_err(Error::SteamProductInfoDecoding)?,
+            response,
             irregular: Default::default(),
         };
 
         // Debugging:
-        let raw = serde_json::from_str::<serde_json::Value>(&stdout).map_err(Error::SteamProductInfoDecoding)?;
         for app_id in app_ids {
             if let Some(ufs) = raw["apps"][app_id.to_string()]["ufs"]["save_files"].as_object() {
                 let keys: Vec<_> = ufs.keys().collect();
@@ -272,6 +529,31 @@ impl ProductInfo {
                         println!("[Steam] Unknown override key: {}", key);
                     }
                 }
+                for transform in ufs
+                    .values()
+                    .filter_map(|x| x["path_transforms"].as_object())
+                    .flat_map(|x| x.values())
+                {
+                    let Some(transform) = transform.as_object() else {
+                        continue;
+                    };
+                    for key in transform.keys() {
+                        if !["find", "replace"].contains(&key.as_str()) {
+                            info.irregular.insert(*app_id);
+                            println!("[Steam] Unknown path transform key in app {}: {}", app_id, key);
+                        }
+                    }
+                    if let Some(replace) = transform.get("replace").and_then(|x| x.as_str()) {
+                        let parsed = CloudTransform {
+                            find: String::new(),
+                            replace: replace.to_string(),
+                        };
+                        if !parsed.is_supported() {
+                            info.irregular.insert(*app_id);
+                            println!("[Steam] Unsupported path transform in app {}: {}", app_id, replace);
+                        }
+                    }
+                }
             }
         }
 
@@ -341,12 +623,14 @@ mod product_info {
     pub struct App {
         pub common: AppCommon,
         pub config: AppConfig,
+        pub extended: AppExtended,
         pub ufs: AppUfs,
     }
 
     #[derive(Debug, Default, Clone, serde::Deserialize)]
     #[serde(default)]
     pub struct AppCommon {
+        pub name: Option<String>,
         pub name_localized: BTreeMap<String, String>,
     }
 
@@ -357,6 +641,13 @@ mod product_info {
         pub launch: BTreeMap<String, AppLaunch>,
     }
 
+    #[derive(Debug, Default, Clone, serde::Deserialize)]
+    #[serde(default)]
+    pub struct AppExtended {
+        pub demoofappid: Option<String>,
+        pub listofdlc: Option<String>,
+    }
+
     #[derive(Debug, Default, Clone, serde::Deserialize)]
     #[serde(default)]
     pub struct AppLaunch {
@@ -375,6 +666,8 @@ mod product_info {
         pub osarch: Option<String>,
         pub oslist: Option<String>,
         pub ownsdlc: Option<String>,
+        pub steamdeck: Option<String>,
+        pub vrmode: Option<String>,
     }
 
     #[derive(Debug, Default, Clone, serde::Deserialize)]
@@ -424,6 +717,31 @@ mod product_info {
 }
 
 impl SteamCacheEntry {
+    /// Whether there's any sign of a native Linux version.
+    pub fn supports_linux(&self) -> bool {
+        self.launch
+            .iter()
+            .any(|x| x.config.oslist.as_deref().is_some_and(|x| x.contains("linux")))
+            || self.cloud.saves.iter().any(|x| {
+                x.root.to_lowercase().starts_with("linux")
+                    || x.platforms.iter().any(|x| x.eq_ignore_ascii_case("linux"))
+            })
+            || self.cloud.overrides.iter().any(|x| {
+                x.os.as_deref().is_some_and(|x| x.eq_ignore_ascii_case("linux"))
+                    || x.use_instead
+                        .as_deref()
+                        .is_some_and(|x| x.to_lowercase().starts_with("linux"))
+            })
+    }
+
+    fn remember_install_dirs(&mut self, previous: &Self) {
+        self.old_install_dirs.extend(previous.old_install_dirs.iter().cloned());
+        self.old_install_dirs.extend(previous.install_dir.iter().cloned());
+        if let Some(current) = &self.install_dir {
+            self.old_install_dirs.remove(current);
+        }
+    }
+
     fn parse_app(app_id: u32, info: &ProductInfo) -> Result<Option<Self>, Error> {
         let Some(app) = info.response.apps.get(&app_id.to_string()).cloned() else {
             eprintln!("Steam: {app_id} - no results");
@@ -445,6 +763,8 @@ impl SteamCacheEntry {
                     osarch: x.config.osarch,
                     oslist: x.config.oslist,
                     ownsdlc: x.config.ownsdlc,
+                    steamdeck: x.config.steamdeck,
+                    vrmode: x.config.vrmode,
                 },
             })
             .filter(|x| !x.is_empty())
@@ -493,40 +813,117 @@ impl SteamCacheEntry {
             state: State::Handled,
             irregular: info.irregular.contains(&app_id),
             cloud,
+            demo_of: app
+                .extended
+                .demoofappid
+                .and_then(|x| x.trim().parse::<u32>().ok())
+                .filter(|x| *x > 0 && *x != app_id),
+            dlc: app
+                .extended
+                .listofdlc
+                .map(|x| {
+                    x.split(',')
+                        .filter_map(|x| x.trim().parse::<u32>().ok())
+                        .filter(|x| *x > 0)
+                        .collect()
+                })
+                .unwrap_or_default(),
             install_dir: app.config.installdir,
+            old_install_dirs: Default::default(),
+            name: app.common.name.map(|x| x.trim().to_string()).filter(|x| !x.is_empty()),
             name_localized: app.common.name_localized,
             launch,
         }))
     }
 }
 
+/// Where each Steam Auto-Cloud root lives in manifest terms.
+pub static STEAM_ROOTS: Lazy<SteamRoots> = Lazy::new(|| SteamRoots::load().unwrap());
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SteamRoots(pub BTreeMap<String, Option<String>>);
+
+impl ResourceFile for SteamRoots {
+    const FILE_NAME: &'static str = "data/steam-roots.yaml";
+}
+
+impl SteamRoots {
+    pub fn is_known(&self, value: &str) -> bool {
+        self.0.contains_key(&value.to_lowercase())
+    }
+
+    /// Mapped roots that don't start with one of the manifest's placeholders.
+    pub fn invalid_mappings(&self) -> Vec<(&String, &String)> {
+        self.0
+            .iter()
+            .filter_map(|(root, mapped)| mapped.as_ref().map(|mapped| (root, mapped)))
+            .filter(|(_, mapped)| {
+                !placeholder::ALL
+                    .iter()
+                    .any(|x| *mapped == x || mapped.starts_with(&format!("{x}/")))
+            })
+            .collect()
+    }
+}
+
 pub fn parse_root(value: &str) -> Option<&'static str> {
-    match value.to_lowercase().as_ref() {
-        "gameinstall" => Some(placeholder::BASE),
-        "linuxhome" => Some(placeholder::HOME),
-        "linuxxdgconfighome" => Some(placeholder::XDG_CONFIG),
-        // TODO: What does this mean?
-        "linuxxdgdataconfig" => None,
-        "linuxxdgdatahome" => Some(placeholder::XDG_DATA),
-        "macappsupport" => Some("<home>/Library/Application Support"),
-        "macdocuments" => Some("<home>/Documents"),
-        "machome" => Some(placeholder::HOME),
-        // TODO: Where does this map on each OS?
-        // The manifest probably needs a new placeholder for it.
-        "steamclouddocuments" => None,
-        "winappdataroaming" => Some(placeholder::WIN_APP_DATA),
-        "winappdatalocal" => Some(placeholder::WIN_LOCAL_APP_DATA),
-        "winappdatalocallow" => Some("<home>/AppData/LocalLow"),
-        "windowshome" => Some(placeholder::HOME),
-        "winmydocuments" => Some(placeholder::WIN_DOCUMENTS),
-        "winsavedgames" => Some("<home>/Saved Games"),
-        _ => {
+    match STEAM_ROOTS.0.get(&value.to_lowercase()) {
+        Some(root) => root.as_deref(),
+        None => {
             println!("[Steam] unknown root: {}", value);
             None
         }
     }
 }
 
+/// Find which apps use each root that we can't map yet.
+pub fn find_unmapped_roots(steam_cache: &SteamCache) -> BTreeMap<String, BTreeSet<u32>> {
+    let mut counts = BTreeMap::<String, BTreeSet<u32>>::new();
+    for (app_id, info) in &steam_cache.0 {
+        let roots = info
+            .cloud
+            .saves
+            .iter()
+            .map(|x| &x.root)
+            .chain(info.cloud.overrides.iter().map(|x| &x.root))
+            .chain(info.cloud.overrides.iter().filter_map(|x| x.use_instead.as_ref()));
+        for root in roots {
+            let mapped = STEAM_ROOTS.0.get(&root.to_lowercase()).is_some_and(|x| x.is_some());
+            if !mapped {
+                counts.entry(root.to_lowercase()).or_default().insert(*app_id);
+            }
+        }
+    }
+    counts
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RootUsage {
+    pub root: String,
+    /// Either `unmapped` (listed in the roots file without a mapping) or `unknown`.
+    pub status: &'static str,
+    pub apps: usize,
+    pub examples: Vec<u32>,
+}
+
+/// Count how many apps use each root that we can't map yet, most used first.
+pub fn audit_roots(steam_cache: &SteamCache) -> Vec<RootUsage> {
+    find_unmapped_roots(steam_cache)
+        .into_iter()
+        .sorted_by_key(|(root, apps)| (std::cmp::Reverse(apps.len()), root.clone()))
+        .map(|(root, apps)| RootUsage {
+            status: if STEAM_ROOTS.is_known(&root) {
+                "unmapped"
+            } else {
+                "unknown"
+            },
+            apps: apps.len(),
+            examples: apps.into_iter().take(5).collect(),
+            root,
+        })
+        .collect()
+}
+
 pub fn parse_platform(value: &str) -> Option<Os> {
     match value.to_lowercase().as_ref() {
         "linux" => Some(Os::Linux),
@@ -540,22 +937,206 @@ pub fn parse_platform(value: &str) -> Option<Os> {
     }
 }
 
-pub fn parse_os_comparison(os: Option<String>, comparison: Option<String>) -> Option<Os> {
+/// Returns the OSes that an override applies to.
+/// This is empty if the override doesn't restrict the OS.
+pub fn parse_os_comparison(os: Option<String>, comparison: Option<String>) -> Vec<Os> {
+    const STEAM_OSES: [Os; 3] = [Os::Windows, Os::Mac, Os::Linux];
+
     let comparison = comparison.unwrap_or_else(|| "=".to_string());
     let os = os.map(|x| x.to_lowercase()).unwrap_or_default();
 
-    match (comparison.as_ref(), os.as_ref()) {
-        ("=", "windows") => Some(Os::Windows),
-        ("=", "linux") => Some(Os::Linux),
-        ("=", "macos") => Some(Os::Mac),
-        (x, _) if x != "=" => {
+    let os = match os.as_ref() {
+        "windows" => Os::Windows,
+        "linux" => Os::Linux,
+        "macos" => Os::Mac,
+        "" => return vec![],
+        x => {
+            println!("[Steam] unknown OS: {}", x);
+            return vec![];
+        }
+    };
+
+    match comparison.as_ref() {
+        "=" => vec![os],
+        "!=" => STEAM_OSES.into_iter().filter(|x| *x != os).collect(),
+        x => {
             println!("[Steam] unknown OS operator: {}", x);
-            None
+            vec![]
         }
-        (_, x) if !x.is_empty() => {
-            println!("[Steam] unknown OS: {}", x);
-            None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steam_roots_map_to_placeholders() {
+        assert_eq!(Vec::<(&String, &String)>::new(), STEAM_ROOTS.invalid_mappings());
+        assert_eq!(Some(placeholder::XDG_CONFIG), parse_root("LinuxXdgDataConfig"));
+        assert_eq!(Some(placeholder::WIN_DOCUMENTS), parse_root("WinMyDocuments"));
+    }
+
+    fn outdated(app_ids: &[u32]) -> SteamCache {
+        SteamCache(
+            app_ids
+                .iter()
+                .map(|x| {
+                    (
+                        *x,
+                        SteamCacheEntry {
+                            state: State::Outdated,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn failed_apps_are_isolated() {
+        let mut cache = outdated(&[1, 2, 3, 4]);
+        let calls = std::cell::Cell::new(0);
+        let fetch = |app_ids: &[u32]| {
+            calls.set(calls.get() + 1);
+            if app_ids.contains(&3) {
+                Err(Error::SteamProductInfoDecoding(
+                    serde_json::from_str::<u32>("").unwrap_err(),
+                ))
+            } else {
+                Ok(ProductInfo {
+                    response: Default::default(),
+                    irregular: Default::default(),
+                })
+            }
+        };
+
+        let result = cache.refresh_batch(&[1, 2, 3, 4], &mut Autosave::new(1000, 1000), &fetch);
+
+        assert!(result.is_ok());
+        assert_eq!(5, calls.get());
+        for (app_id, info) in &cache.0 {
+            assert_eq!(State::Handled, info.state);
+            assert_eq!(*app_id == 3, info.irregular);
         }
-        _ => None,
+    }
+
+    #[test]
+    fn unavailable_steam_stops_the_refresh() {
+        let mut cache = outdated(&[1, 2, 3, 4]);
+        let calls = std::cell::Cell::new(0);
+        let fetch = |_: &[u32]| {
+            calls.set(calls.get() + 1);
+            Err(Error::SteamProductInfo)
+        };
+
+        let result = cache.refresh_batch(&[1, 2, 3, 4], &mut Autosave::new(1000, 1000), &fetch);
+
+        assert!(matches!(result, Err(Error::SteamProductInfo)));
+        assert_eq!(1, calls.get());
+        for info in cache.0.values() {
+            assert_eq!(State::Outdated, info.state);
+            assert!(!info.irregular);
+        }
+    }
+
+    #[test]
+    fn co_primary_steam_ids_are_queued() {
+        let mut wiki_cache = WikiCache(BTreeMap::from([(
+            "Game".to_string(),
+            crate::wiki::WikiCacheEntry {
+                state: State::Updated,
+                steam: Some(1),
+                ..Default::default()
+            },
+        )]));
+        let overrides = ManifestOverride(BTreeMap::from([(
+            "Game".to_string(),
+            crate::manifest::OverrideGame {
+                co_primary_steam: BTreeSet::from([2]),
+                ..Default::default()
+            },
+        )]));
+        let mut steam_cache = SteamCache::default();
+
+        steam_cache.transition_states_from(&mut wiki_cache, &overrides);
+
+        assert_eq!(State::Handled, wiki_cache.0["Game"].state);
+        assert_eq!(
+            vec![(1, State::Outdated), (2, State::Outdated)],
+            steam_cache.0.iter().map(|(k, v)| (*k, v.state)).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn side_steam_ids_are_queued() {
+        let mut wiki_cache = WikiCache(BTreeMap::from([(
+            "Game".to_string(),
+            crate::wiki::WikiCacheEntry {
+                steam: Some(1),
+                steam_side: BTreeSet::from([2, 3]),
+                ..Default::default()
+            },
+        )]));
+        let mut steam_cache = SteamCache(BTreeMap::from([
+            (1, SteamCacheEntry::default()),
+            (2, SteamCacheEntry::default()),
+        ]));
+
+        steam_cache.transition_states_from(&mut wiki_cache, &ManifestOverride::default());
+
+        assert_eq!(
+            vec![(1, State::Handled), (2, State::Handled), (3, State::Outdated)],
+            steam_cache.0.iter().map(|(k, v)| (*k, v.state)).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn can_classify_launch_entries() {
+        let cases = [
+            ("Game.exe", None, None, LaunchKind::Game),
+            ("TheObserver.exe", None, None, LaunchKind::Game),
+            ("ServerRoom.exe", None, None, LaunchKind::Game),
+            ("Crashday.exe", None, None, LaunchKind::Game),
+            ("Settlers.exe", None, None, LaunchKind::Game),
+            ("Game.exe", None, Some("Crash Bandicoot mode"), LaunchKind::Game),
+            ("Game.exe", None, Some("Launch with default settings"), LaunchKind::Game),
+            ("GameConfig.exe", Some("default"), None, LaunchKind::Game),
+            ("UnityCrashHandler64.exe", None, None, LaunchKind::CrashHandler),
+            (
+                "Engine/Binaries/CrashReportClient.exe",
+                None,
+                None,
+                LaunchKind::CrashHandler,
+            ),
+            ("bin/Benchmark.exe", None, None, LaunchKind::Benchmark),
+            ("DedicatedServer.exe", None, None, LaunchKind::Server),
+            ("game_server.x86_64", None, None, LaunchKind::Server),
+            ("Launcher.exe", Some("server"), None, LaunchKind::Server),
+            ("GameSettings.exe", None, None, LaunchKind::Tool),
+            ("Setup.exe", None, None, LaunchKind::Tool),
+            ("Launcher.exe", Some("config"), None, LaunchKind::Tool),
+        ];
+
+        for (executable, r#type, description, expected) in cases {
+            let launch = Launch {
+                executable: Some(executable.to_string()),
+                r#type: r#type.map(|x: &str| x.to_string()),
+                description: description.map(|x: &str| x.to_string()),
+                ..Default::default()
+            };
+            assert_eq!(expected, launch.kind(), "{}", executable);
+        }
+
+        let launch = Launch {
+            executable: Some("Game.exe".to_string()),
+            config: LaunchConfig {
+                vrmode: Some("1".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(LaunchKind::Vr, launch.kind());
     }
 }